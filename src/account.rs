@@ -0,0 +1,123 @@
+use crate::error::TtsError;
+use crate::types::{AudioFormat, SpeechRequest};
+use serde::{Deserialize, Serialize};
+
+const TIERS: [&str; 6] = ["free", "starter", "creator", "pro", "scale", "business"];
+// concurrent request limits per tier, in TIERS order
+const CONCURRENCY_LIMITS: [usize; 6] = [2, 3, 5, 10, 15, 15];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelLanguage {
+    pub language_id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub model_id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub can_do_text_to_speech: bool,
+    #[serde(default)]
+    pub requires_alpha_access: bool,
+    pub max_characters_request_free_user: Option<usize>,
+    pub max_characters_request_subscribed_user: Option<usize>,
+    #[serde(default)]
+    pub languages: Vec<ModelLanguage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subscription {
+    pub tier: String,
+    #[serde(default)]
+    pub character_count: u64,
+    #[serde(default)]
+    pub character_limit: u64,
+    #[serde(default)]
+    pub can_use_instant_voice_cloning: bool,
+    #[serde(default)]
+    pub can_use_professional_voice_cloning: bool,
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountCapabilities {
+    pub models: Vec<ModelInfo>,
+    pub subscription: Subscription,
+}
+
+impl AccountCapabilities {
+    pub fn is_free_tier(&self) -> bool {
+        self.subscription.tier.eq_ignore_ascii_case("free")
+    }
+
+    pub fn format_allowed(&self, format: &AudioFormat) -> bool {
+        let Some(required) = required_tier(format) else {
+            return true;
+        };
+        match tier_rank(&self.subscription.tier) {
+            Some(rank) => Some(rank) >= tier_rank(required),
+            None => true,
+        }
+    }
+
+    pub fn concurrency_limit(&self) -> Option<usize> {
+        tier_rank(&self.subscription.tier).map(|rank| CONCURRENCY_LIMITS[rank])
+    }
+
+    pub fn model(&self, model_id: &str) -> Option<&ModelInfo> {
+        self.models.iter().find(|model| model.model_id == model_id)
+    }
+
+    pub fn max_characters(&self, model_id: &str) -> Option<usize> {
+        let model = self.model(model_id)?;
+        if self.is_free_tier() {
+            model.max_characters_request_free_user
+        } else {
+            model.max_characters_request_subscribed_user
+        }
+    }
+
+    // rejects requests the API is known to refuse for this account; unknown models pass
+    // through so newly released models keep working with a stale cache
+    pub fn check(&self, request: &SpeechRequest) -> Result<(), TtsError> {
+        let format = request.output_format.clone().unwrap_or_default();
+        if !self.format_allowed(&format) {
+            return Err(TtsError::FormatNotAllowed {
+                format: format.as_str().to_string(),
+                required_tier: required_tier(&format).unwrap_or_default().to_string(),
+            });
+        }
+
+        let model_id = request.model.as_str();
+        let Some(model) = self.model(model_id) else {
+            return Ok(());
+        };
+        if !model.can_do_text_to_speech || model.requires_alpha_access {
+            return Err(TtsError::ModelNotAvailable(model_id.to_string()));
+        }
+
+        let length = request.text.chars().count();
+        if let Some(limit) = self.max_characters(model_id) {
+            if length > limit {
+                return Err(TtsError::ExceedsPlanLimit { length, limit });
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn required_tier(format: &AudioFormat) -> Option<&'static str> {
+    match format {
+        AudioFormat::Mp3_44100_192 => Some("creator"),
+        AudioFormat::Pcm44100 => Some("pro"),
+        _ => None,
+    }
+}
+
+// None for tiers this crate doesn't know about, e.g. enterprise or newly introduced plans
+fn tier_rank(tier: &str) -> Option<usize> {
+    let tier = tier.to_ascii_lowercase();
+    TIERS.iter().position(|t| tier == *t)
+}
@@ -0,0 +1,34 @@
+use crate::error::TtsError;
+use crate::types::Voice;
+use crate::util::kv_get;
+use hyperware_process_lib::kv::{self, Kv};
+use hyperware_process_lib::our;
+
+pub struct VoiceAliases {
+    kv: Kv<String, String>,
+}
+
+impl VoiceAliases {
+    // aliases live in a KV database owned by the calling package, so every process in it
+    // resolves the same alias to the same voice
+    pub fn open(db: &str) -> Result<Self, TtsError> {
+        let kv = kv::open(our().package_id(), db, None).map_err(|e| TtsError::Kv(e.to_string()))?;
+        Ok(Self { kv })
+    }
+
+    pub fn set(&self, alias: &str, voice: &Voice) -> Result<(), TtsError> {
+        self.kv
+            .set(&alias.to_string(), &voice.as_voice_id().to_string(), None)
+            .map_err(|e| TtsError::Kv(e.to_string()))
+    }
+
+    pub fn get(&self, alias: &str) -> Option<Voice> {
+        kv_get(&self.kv, &alias.to_string()).map(|voice_id| Voice::from_voice_id(&voice_id))
+    }
+
+    pub fn remove(&self, alias: &str) -> Result<(), TtsError> {
+        self.kv
+            .delete(&alias.to_string(), None)
+            .map_err(|e| TtsError::Kv(e.to_string()))
+    }
+}
@@ -0,0 +1,215 @@
+use crate::types::AudioFormat;
+
+// MPEG-1 and MPEG-2 Layer III bitrate tables (kbps), indexed by the header's bitrate index
+const MPEG1_BITRATES: [u32; 15] = [
+    0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+];
+const MPEG2_BITRATES: [u32; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+
+// (sample rate, bitrate in kbps) of the mp3 formats
+pub(crate) fn mp3_parameters(format: &AudioFormat) -> Option<(u32, u32)> {
+    match format {
+        AudioFormat::Mp3_22050_32 => Some((22050, 32)),
+        AudioFormat::Mp3_44100_32 => Some((44100, 32)),
+        AudioFormat::Mp3_44100_64 => Some((44100, 64)),
+        AudioFormat::Mp3_44100_96 => Some((44100, 96)),
+        AudioFormat::Mp3_44100_128 => Some((44100, 128)),
+        AudioFormat::Mp3_44100_192 => Some((44100, 192)),
+        _ => None,
+    }
+}
+
+pub fn pcm_sample_rate(format: &AudioFormat) -> Option<u32> {
+    match format {
+        AudioFormat::Pcm16000 => Some(16000),
+        AudioFormat::Pcm22050 => Some(22050),
+        AudioFormat::Pcm24000 => Some(24000),
+        AudioFormat::Pcm44100 => Some(44100),
+        _ => None,
+    }
+}
+
+// audio in `format` that plays as at least `duration_ms` of silence; mp3 silence is rounded
+// up to whole frames (about 26 ms each)
+pub fn silence(format: &AudioFormat, duration_ms: u64) -> Vec<u8> {
+    if let Some(rate) = pcm_sample_rate(format) {
+        let samples = rate as u64 * duration_ms / 1000;
+        return vec![0; samples as usize * 2];
+    }
+    match mp3_parameters(format) {
+        Some((rate, bitrate)) => silent_mp3(rate, bitrate, duration_ms),
+        // µ-law encodes zero amplitude as 0xff
+        None => vec![0xff; (8000 * duration_ms / 1000) as usize],
+    }
+}
+
+fn silent_mp3(rate: u32, bitrate: u32, duration_ms: u64) -> Vec<u8> {
+    let mpeg1 = rate == 44100;
+    let (bitrates, samples_per_frame, frame_coefficient) = if mpeg1 {
+        (&MPEG1_BITRATES, 1152, 144)
+    } else {
+        (&MPEG2_BITRATES, 576, 72)
+    };
+    let bitrate_index = bitrates.iter().position(|b| *b == bitrate).unwrap_or(1) as u8;
+    let frame_len = (frame_coefficient * bitrate * 1000 / rate) as usize;
+
+    // sync word, Layer III, no CRC; sample rate index 0 is 44.1 kHz for MPEG-1 and 22.05 kHz
+    // for MPEG-2; single-channel mode. All-zero side info and main data decode as silence.
+    let mut frame = vec![0u8; frame_len];
+    frame[0] = 0xff;
+    frame[1] = if mpeg1 { 0xfb } else { 0xf3 };
+    frame[2] = bitrate_index << 4;
+    frame[3] = 0xc0;
+
+    let frame_ms = samples_per_frame as f64 * 1000.0 / rate as f64;
+    let frames = (duration_ms as f64 / frame_ms).ceil() as usize;
+    frame.repeat(frames)
+}
+
+// a sine tone at a fifth of full scale; there is no mp3 encoder here, so mp3 formats get
+// silence of the same duration
+pub fn tone(format: &AudioFormat, frequency_hz: f32, duration_ms: u64) -> Vec<u8> {
+    let rate = match pcm_sample_rate(format) {
+        Some(rate) => rate,
+        None if format.is_mp3() => return silence(format, duration_ms),
+        None => 8000,
+    };
+    let samples: Vec<i16> = (0..rate as u64 * duration_ms / 1000)
+        .map(|n| {
+            let phase = 2.0 * std::f64::consts::PI * frequency_hz as f64 * n as f64 / rate as f64;
+            (phase.sin() * i16::MAX as f64 * 0.2) as i16
+        })
+        .collect();
+    if pcm_sample_rate(format).is_some() {
+        encode_pcm16(&samples)
+    } else {
+        samples.into_iter().map(encode_ulaw).collect()
+    }
+}
+
+// G.711 µ-law
+fn encode_ulaw(sample: i16) -> u8 {
+    const BIAS: i32 = 0x84;
+    const CLIP: i32 = 32635;
+    let sign = if sample < 0 { 0x80 } else { 0 };
+    let magnitude = (sample as i32).abs().min(CLIP) + BIAS;
+    let exponent = (0..8)
+        .rev()
+        .find(|e| magnitude & (0x80 << e) != 0)
+        .unwrap_or(0);
+    let mantissa = (magnitude >> (exponent + 3)) & 0x0f;
+    !(sign | (exponent << 4) | mantissa) as u8
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResampleQuality {
+    // linear interpolation; cheap, but aliases when downsampling
+    Fast,
+    #[default]
+    Balanced,
+    Best,
+}
+
+impl ResampleQuality {
+    // half-width of the windowed-sinc kernel, in input samples
+    fn half_width(&self) -> usize {
+        match self {
+            ResampleQuality::Fast => 0,
+            ResampleQuality::Balanced => 8,
+            ResampleQuality::Best => 32,
+        }
+    }
+}
+
+pub fn decode_pcm16(pcm: &[u8]) -> Vec<i16> {
+    pcm.chunks_exact(2)
+        .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]))
+        .collect()
+}
+
+pub fn encode_pcm16(samples: &[i16]) -> Vec<u8> {
+    samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+}
+
+// mixes `outgoing` into the start of `incoming` (16-bit PCM at the same rate), fading it out
+// as `incoming` fades in; the result is as long as `incoming`
+pub fn crossfade_pcm(outgoing: &[u8], incoming: &[u8]) -> Vec<u8> {
+    let outgoing = decode_pcm16(outgoing);
+    let mut samples = decode_pcm16(incoming);
+    let len = outgoing.len().min(samples.len());
+    for (i, (sample, out)) in samples.iter_mut().zip(&outgoing).enumerate() {
+        let t = (i + 1) as f32 / (len + 1) as f32;
+        let mixed = *out as f32 * (1.0 - t) + *sample as f32 * t;
+        *sample = mixed.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+    }
+    encode_pcm16(&samples)
+}
+
+// resamples 16-bit little-endian mono PCM, as returned for the pcm_* formats
+pub fn resample_pcm(pcm: &[u8], from_rate: u32, to_rate: u32, quality: ResampleQuality) -> Vec<u8> {
+    encode_pcm16(&resample(&decode_pcm16(pcm), from_rate, to_rate, quality))
+}
+
+pub fn resample(
+    samples: &[i16],
+    from_rate: u32,
+    to_rate: u32,
+    quality: ResampleQuality,
+) -> Vec<i16> {
+    if from_rate == to_rate || samples.is_empty() || from_rate == 0 || to_rate == 0 {
+        return samples.to_vec();
+    }
+
+    let step = from_rate as f64 / to_rate as f64;
+    let output_len = (samples.len() as u64 * to_rate as u64 / from_rate as u64) as usize;
+    let half_width = quality.half_width();
+    // when downsampling, the kernel's cutoff drops to the output Nyquist frequency
+    let cutoff = (to_rate as f64 / from_rate as f64).min(1.0);
+    let sample = |i: isize| -> f64 {
+        samples
+            .get(i.clamp(0, samples.len() as isize - 1) as usize)
+            .copied()
+            .unwrap_or_default() as f64
+    };
+
+    (0..output_len)
+        .map(|n| {
+            let position = n as f64 * step;
+            let base = position.floor() as isize;
+            let value = if half_width == 0 {
+                let fraction = position - base as f64;
+                sample(base) * (1.0 - fraction) + sample(base + 1) * fraction
+            } else {
+                let reach = (half_width as f64 / cutoff).ceil() as isize;
+                let mut sum = 0.0;
+                let mut weights = 0.0;
+                for i in (base - reach + 1)..=(base + reach) {
+                    let distance = (position - i as f64) * cutoff;
+                    let window = distance / half_width as f64;
+                    if window.abs() >= 1.0 {
+                        continue;
+                    }
+                    let weight =
+                        sinc(distance) * 0.5 * (1.0 + (std::f64::consts::PI * window).cos());
+                    sum += sample(i) * weight;
+                    weights += weight;
+                }
+                if weights == 0.0 {
+                    0.0
+                } else {
+                    sum / weights
+                }
+            };
+            value.round().clamp(i16::MIN as f64, i16::MAX as f64) as i16
+        })
+        .collect()
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        let x = std::f64::consts::PI * x;
+        x.sin() / x
+    }
+}
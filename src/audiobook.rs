@@ -0,0 +1,228 @@
+use crate::client::{send_stitched, SpeechClient};
+use crate::document::settings_hash;
+use crate::error::TtsError;
+use crate::hash::sha256_hex;
+use crate::id3::Id3Tags;
+use crate::request_presets::RequestPreset;
+use crate::text::chunk_text;
+use crate::types::{AudioFormat, SpeechRequest, TtsModel, Voice, VoiceSettings};
+use crate::util::{capped_concurrency, load_json_or_default, write_file};
+use futures::stream::{self, StreamExt};
+use hyperware_process_lib::vfs;
+use serde::{Deserialize, Serialize};
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, Clone)]
+pub struct Chapter {
+    pub title: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudiobookManifest {
+    pub title: Option<String>,
+    pub format: AudioFormat,
+    pub chapters: Vec<ChapterManifest>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChapterManifest {
+    pub index: usize,
+    pub title: String,
+    pub path: String,
+    pub duration_ms: u64,
+    pub sha256: String,
+    pub text_sha256: String,
+    // covers the text, the titles in the chapter's tags, and the render settings, so changing
+    // any of them re-synthesizes the chapter on resume
+    #[serde(default)]
+    pub key: String,
+    pub request_ids: Vec<String>,
+}
+
+pub struct AudiobookBuilder<'a> {
+    client: &'a SpeechClient,
+    directory: String,
+    title: Option<String>,
+    template: SpeechRequest,
+    chapters: Vec<Chapter>,
+    concurrency: usize,
+}
+
+impl<'a> AudiobookBuilder<'a> {
+    pub(crate) fn new(client: &'a SpeechClient, directory: String) -> Self {
+        Self {
+            client,
+            directory: directory.trim_end_matches('/').to_string(),
+            title: None,
+            template: SpeechRequest::default(),
+            chapters: Vec::new(),
+            concurrency: 1,
+        }
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn chapter(mut self, title: impl Into<String>, text: impl Into<String>) -> Self {
+        self.chapters.push(Chapter {
+            title: title.into(),
+            text: text.into(),
+        });
+        self
+    }
+
+    pub fn chapters(mut self, chapters: Vec<Chapter>) -> Self {
+        self.chapters.extend(chapters);
+        self
+    }
+
+    pub fn model(mut self, model: TtsModel) -> Self {
+        self.template.model = model;
+        self
+    }
+
+    pub fn voice(mut self, voice: Voice) -> Self {
+        self.template.voice = voice;
+        self
+    }
+
+    pub fn voice_settings(mut self, settings: VoiceSettings) -> Self {
+        self.template.voice_settings = Some(settings);
+        self
+    }
+
+    pub fn output_format(mut self, format: AudioFormat) -> Self {
+        self.template.output_format = Some(format);
+        self
+    }
+
+    pub fn language_code(mut self, code: impl Into<String>) -> Self {
+        self.template.language_code = Some(code.into());
+        self
+    }
+
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.template.seed = Some(seed);
+        self
+    }
+
+    pub fn concurrency(mut self, chapters: usize) -> Self {
+        self.concurrency = chapters.max(1);
+        self
+    }
+
+    pub async fn execute(self) -> Result<AudiobookManifest, TtsError> {
+        let format = self.template.output_format.clone().unwrap_or_default();
+        let manifest_path = format!("{}/{}", self.directory, MANIFEST_FILE);
+
+        let settings_sha256 = settings_hash(&RequestPreset::from_request(&self.template))?;
+        vfs::open_dir(&self.directory, true, None).map_err(|e| TtsError::Vfs(e.to_string()))?;
+        let previous = load_json_or_default::<Option<AudiobookManifest>>(&manifest_path)?
+            .filter(|m| m.format == format);
+
+        let mut manifest = AudiobookManifest {
+            title: self.title.clone(),
+            format: format.clone(),
+            chapters: Vec::new(),
+        };
+
+        // chapters render concurrently but complete in order, so the manifest on disk is
+        // always a prefix of the book and resuming stays correct
+        let concurrency = capped_concurrency(self.concurrency, self.client.account_capabilities());
+        let mut chapters = stream::iter(self.chapters.iter().enumerate())
+            .map(|(index, chapter)| {
+                self.render_chapter(index, chapter, &format, &settings_sha256, previous.as_ref())
+            })
+            .buffered(concurrency);
+
+        while let Some(chapter) = chapters.next().await {
+            manifest.chapters.push(chapter?);
+
+            let manifest_json = serde_json::to_vec_pretty(&manifest)
+                .map_err(|e| TtsError::SerializationError(e.to_string()))?;
+            write_file(&manifest_path, &manifest_json)?;
+        }
+
+        Ok(manifest)
+    }
+
+    async fn render_chapter(
+        &self,
+        index: usize,
+        chapter: &Chapter,
+        format: &AudioFormat,
+        settings_sha256: &str,
+        previous: Option<&AudiobookManifest>,
+    ) -> Result<ChapterManifest, TtsError> {
+        let text_sha256 = sha256_hex(chapter.text.as_bytes());
+        let key = sha256_hex(
+            format!(
+                "{}\n{}\n{}\n{}",
+                settings_sha256,
+                self.title.as_deref().unwrap_or_default(),
+                chapter.title,
+                chapter.text
+            )
+            .as_bytes(),
+        );
+
+        let completed =
+            previous.and_then(|m| m.chapters.iter().find(|c| c.index == index && c.key == key));
+        if let Some(completed) = completed {
+            return Ok(completed.clone());
+        }
+
+        let (audio, request_ids) = self.synthesize_chapter(&chapter.text).await?;
+        // estimated from the audio alone, since the tags carry no playback time
+        let duration_ms = format.estimated_duration_ms(audio.len());
+        let audio = if format.is_mp3() {
+            let mut tags = Id3Tags::new()
+                .title(chapter.title.clone())
+                .artist(self.template.voice.name())
+                .track(index as u32 + 1);
+            tags.album = self.title.clone();
+            tags.embed(&audio)
+        } else {
+            audio
+        };
+
+        let path = format!(
+            "{}/chapter-{:03}.{}",
+            self.directory,
+            index + 1,
+            format.extension()
+        );
+        write_file(&path, &audio)?;
+
+        Ok(ChapterManifest {
+            index,
+            title: chapter.title.clone(),
+            path,
+            duration_ms,
+            sha256: sha256_hex(&audio),
+            text_sha256,
+            key,
+            request_ids,
+        })
+    }
+
+    // chunked after preprocessing, as `send_chunked` does, since verbalization, pronunciation
+    // rules, and SSML translation can lengthen the text
+    async fn synthesize_chapter(&self, text: &str) -> Result<(Vec<u8>, Vec<String>), TtsError> {
+        let template = self.client.prepare(SpeechRequest {
+            text: text.to_string(),
+            ..self.template.clone()
+        })?;
+        let max_length = self.client.max_input_length(&template.model);
+        let chunks = chunk_text(&template.text, max_length);
+        let stitched = send_stitched(&template, &chunks, |_, request| {
+            self.client.send_prepared(request)
+        })
+        .await?;
+        Ok((stitched.audio, stitched.request_ids))
+    }
+}
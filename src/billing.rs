@@ -0,0 +1,19 @@
+use crate::types::TtsModel;
+
+// characters are counted as Unicode scalar values, spaces and punctuation included;
+// leading and trailing whitespace is not billed
+pub fn character_count(text: &str) -> u64 {
+    text.trim().chars().count() as u64
+}
+
+// Flash and Turbo models bill half a credit per character on self-serve plans
+pub fn credit_multiplier(model: &TtsModel) -> f64 {
+    match model {
+        TtsModel::ElevenFlashV25 | TtsModel::ElevenTurboV25 => 0.5,
+        TtsModel::ElevenV3 | TtsModel::ElevenMultilingualV2 => 1.0,
+    }
+}
+
+pub fn billable_characters(text: &str, model: &TtsModel) -> u64 {
+    (character_count(text) as f64 * credit_multiplier(model)).ceil() as u64
+}
@@ -0,0 +1,121 @@
+use crate::hash::sha256_hex;
+use crate::types::{SpeechRequest, SpeechRequestJson, SpeechResponse};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+#[derive(Default)]
+struct CacheEntries {
+    responses: HashMap<String, SpeechResponse>,
+    // insertion order, oldest first, for eviction
+    order: VecDeque<String>,
+}
+
+pub struct ResponseCache {
+    capacity: usize,
+    entries: Mutex<CacheEntries>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ResponseCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(CacheEntries::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.lock().responses.len(),
+        }
+    }
+
+    pub fn clear(&self) {
+        let mut entries = self.lock();
+        entries.responses.clear();
+        entries.order.clear();
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<SpeechResponse> {
+        let response = self.lock().responses.get(key).cloned();
+        let counter = if response.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        response
+    }
+
+    pub(crate) fn insert(&self, key: String, response: SpeechResponse) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.lock();
+        if entries.responses.insert(key.clone(), response).is_none() {
+            entries.order.push_back(key);
+        }
+        while entries.responses.len() > self.capacity {
+            let Some(oldest) = entries.order.pop_front() else {
+                break;
+            };
+            entries.responses.remove(&oldest);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CacheEntries> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+// case, runs of whitespace, and spaces before punctuation don't change what is spoken
+pub fn normalize_cache_text(text: &str) -> String {
+    let mut normalized = String::with_capacity(text.len());
+    for word in text.split_whitespace() {
+        let punctuation = word.starts_with(['.', ',', '!', '?', ';', ':']);
+        if !normalized.is_empty() && !punctuation {
+            normalized.push(' ');
+        }
+        normalized.push_str(&word.to_lowercase());
+    }
+    normalized
+}
+
+// covers everything sent to the API, the endpoint, and the ID3 tags applied locally,
+// with the text in normalized form
+pub(crate) fn cache_key(request: &SpeechRequest) -> String {
+    let mut request = request.clone();
+    request.text = normalize_cache_text(&request.text);
+    let voice_id = request.voice.as_voice_id().to_string();
+    let output_format = request.output_format.clone().unwrap_or_default();
+    let query_params = format!("{:?}", request.query_params);
+    let id3_tags = format!("{:?}", request.id3_tags);
+    let timestamps = request.with_timestamps.to_string();
+
+    let json = serde_json::to_string(&SpeechRequestJson::from(request)).unwrap_or_default();
+    let key = [
+        voice_id.as_str(),
+        output_format.as_str(),
+        &query_params,
+        &id3_tags,
+        &timestamps,
+        &json,
+    ]
+    .join("|");
+    sha256_hex(key.as_bytes())
+}
@@ -1,20 +1,106 @@
+use crate::account::{AccountCapabilities, ModelInfo, Subscription};
+use crate::aliases::VoiceAliases;
+use crate::audiobook::AudiobookBuilder;
+use crate::billing::billable_characters;
+use crate::cache::{cache_key, CacheStats, ResponseCache};
+use crate::compare::{ComparisonResult, ComparisonVariant};
+use crate::degradation::DegradationPolicy;
+use crate::delivery::DeliveryPresets;
+use crate::document::{patch_segment, DocumentBuilder, DocumentManifest};
 use crate::error::TtsError;
+use crate::events::RequestEvent;
+use crate::export::CorpusExporter;
+use crate::failover::{FailoverStats, RegionFailover};
+use crate::glossary::Glossary;
+use crate::hash::sha256_hex;
+use crate::html::html_to_speech_text;
+use crate::id3::Id3Tags;
+use crate::offline::{FlushedRequest, OfflineQueue};
+use crate::preset_library::PresetLibrary;
+use crate::presets::VoicePresets;
+use crate::profanity::ProfanityFilter;
+use crate::pronunciation::PronunciationRules;
+use crate::redact::PiiRedactor;
+use crate::repro::{ReplayReport, ReproBundle};
+use crate::retry::{RetryClass, RetryPolicy};
+use crate::sandbox::Sandbox;
+use crate::session::SpeechSession;
+use crate::ssml::apply_ssml;
+use crate::sweep::SweepBuilder;
+use crate::text::chunk_text;
 use crate::types::{
-    ApiErrorResponse, AudioFormat, SpeechRequest, SpeechRequestJson, SpeechResponse,
-    TextNormalization, TtsModel, Voice, VoiceSettings,
+    ApiErrorResponse, AudioFormat, DetailErrorResponse, HedgeConfig, InputLengthPolicy,
+    RawResponse, SpeechRequest, SpeechRequestJson, SpeechResponse, TextNormalization,
+    TimestampsResponse, TtsModel, Voice, VoiceSettings,
 };
+use crate::util::write_file;
+use crate::verbalize::verbalize;
+use crate::voice_chat::{best_pcm_format, voice_chat_frames};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use futures::channel::mpsc::UnboundedSender;
+use futures::future::{join_all, select, Either};
+use futures::stream::{self, Stream};
 use hyperware_process_lib::http::client::{send_request_await_response, HttpClientError};
+use hyperware_process_lib::hyperapp::sleep;
+use hyperware_process_lib::vfs;
 use http::Method;
+use serde::de::DeserializeOwned;
 use std::collections::HashMap;
+use std::future::Future;
+use std::pin::pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
 
-const MAX_INPUT_LENGTH: usize = 5000;
+pub(crate) const MAX_INPUT_LENGTH: usize = 5000;
 const MIN_VOICE_SETTING: f32 = 0.0;
 const MAX_VOICE_SETTING: f32 = 1.0;
+const MIN_SPEED: f32 = 0.7;
+const MAX_SPEED: f32 = 1.2;
+const VOICE_ID_LENGTH: usize = 20;
+// the API accepts at most three previous request ids for stitching
+pub(crate) const MAX_STITCHED_REQUEST_IDS: usize = 3;
+// shorter than any spoken syllable; anything below this is a truncated body
+const MIN_AUDIO_DURATION_MS: u64 = 50;
+// queued requests replayed after each successful request
+const OFFLINE_FLUSH_BATCH: usize = 3;
+const PREVIEW_TEXT: &str =
+    "Hello! This is a preview of my voice. I can narrate stories, read articles aloud, and answer your questions.";
 
 pub struct SpeechClient {
     api_key: String,
     base_url: String,
+    failover: Option<RegionFailover>,
     timeout: u64,
+    fallback_model: Option<TtsModel>,
+    fallback_voice: Option<Voice>,
+    repro_directory: Option<String>,
+    pronunciation_rules: Option<PronunciationRules>,
+    profanity_filter: Option<ProfanityFilter>,
+    pii_redactor: Option<PiiRedactor>,
+    verbalize_numbers: bool,
+    max_response_size: Option<usize>,
+    verify_content: bool,
+    default_headers: HashMap<String, String>,
+    user_agent: Option<String>,
+    retry_policy: RetryPolicy,
+    capabilities: Option<AccountCapabilities>,
+    // billed since the capabilities were captured, which is as current as their usage is
+    characters_used: AtomicU64,
+    degradation_policy: Option<DegradationPolicy>,
+    voice_aliases: Option<VoiceAliases>,
+    voice_presets: Option<VoicePresets>,
+    preset_library: Option<PresetLibrary>,
+    delivery_presets: DeliveryPresets,
+    preview_directory: Option<String>,
+    response_cache: Option<ResponseCache>,
+    corpus_exporter: Option<CorpusExporter>,
+    offline_queue: Option<OfflineQueue>,
+    sandbox: Option<Sandbox>,
+    event_sender: Option<UnboundedSender<RequestEvent>>,
+    event_counter: AtomicU64,
+    input_length_policy: InputLengthPolicy,
+    auto_seed: bool,
 }
 
 impl SpeechClient {
@@ -22,7 +108,36 @@ impl SpeechClient {
         Self {
             api_key: api_key.into(),
             base_url: "https://api.elevenlabs.io".to_string(),
+            failover: None,
             timeout: 60000,
+            fallback_model: None,
+            fallback_voice: None,
+            repro_directory: None,
+            pronunciation_rules: None,
+            profanity_filter: None,
+            pii_redactor: None,
+            verbalize_numbers: false,
+            max_response_size: None,
+            verify_content: true,
+            default_headers: HashMap::new(),
+            user_agent: None,
+            retry_policy: RetryPolicy::default(),
+            capabilities: None,
+            characters_used: AtomicU64::new(0),
+            degradation_policy: None,
+            voice_aliases: None,
+            voice_presets: None,
+            preset_library: None,
+            delivery_presets: DeliveryPresets::default(),
+            preview_directory: None,
+            response_cache: None,
+            corpus_exporter: None,
+            offline_queue: None,
+            sandbox: None,
+            event_sender: None,
+            event_counter: AtomicU64::new(0),
+            input_length_policy: InputLengthPolicy::default(),
+            auto_seed: false,
         }
     }
 
@@ -31,11 +146,225 @@ impl SpeechClient {
         self
     }
 
+    // only connectivity errors (HttpClientError) count towards failing over; API errors
+    // mean the region is up
+    pub fn with_region_failover(mut self, failover: RegionFailover) -> Self {
+        self.failover = Some(failover);
+        self
+    }
+
     pub fn with_timeout(mut self, timeout: u64) -> Self {
         self.timeout = timeout;
         self
     }
 
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.default_headers.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    pub fn with_fallback_model(mut self, model: TtsModel) -> Self {
+        self.fallback_model = Some(model);
+        self
+    }
+
+    pub fn with_fallback_voice(mut self, voice: Voice) -> Self {
+        self.fallback_voice = Some(voice);
+        self
+    }
+
+    pub fn with_repro_directory(mut self, directory: impl Into<String>) -> Self {
+        self.repro_directory = Some(directory.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    pub fn with_pronunciation_rules(mut self, rules: PronunciationRules) -> Self {
+        self.pronunciation_rules = Some(rules);
+        self
+    }
+
+    // glossary terms run as pronunciation aliases, after any rules already configured
+    pub fn with_glossary(mut self, glossary: &Glossary) -> Self {
+        let rules = self.pronunciation_rules.take().unwrap_or_default();
+        self.pronunciation_rules = Some(rules.glossary(glossary));
+        self
+    }
+
+    pub fn with_profanity_filter(mut self, filter: ProfanityFilter) -> Self {
+        self.profanity_filter = Some(filter);
+        self
+    }
+
+    pub fn with_pii_redaction(mut self, redactor: PiiRedactor) -> Self {
+        self.pii_redactor = Some(redactor);
+        self
+    }
+
+    pub fn with_number_verbalization(mut self, enabled: bool) -> Self {
+        self.verbalize_numbers = enabled;
+        self
+    }
+
+    pub fn with_max_response_size(mut self, max_bytes: usize) -> Self {
+        self.max_response_size = Some(max_bytes);
+        self
+    }
+
+    pub fn with_content_verification(mut self, enabled: bool) -> Self {
+        self.verify_content = enabled;
+        self
+    }
+
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    pub fn with_account_capabilities(mut self, capabilities: AccountCapabilities) -> Self {
+        self.capabilities = Some(capabilities);
+        self.characters_used = AtomicU64::new(0);
+        self
+    }
+
+    // takes effect only with account capabilities, which carry the quota
+    pub fn with_degradation_policy(mut self, policy: DegradationPolicy) -> Self {
+        self.degradation_policy = Some(policy);
+        self
+    }
+
+    pub fn with_voice_aliases(mut self, aliases: VoiceAliases) -> Self {
+        self.voice_aliases = Some(aliases);
+        self
+    }
+
+    pub fn with_voice_presets(mut self, presets: VoicePresets) -> Self {
+        self.voice_presets = Some(presets);
+        self
+    }
+
+    pub fn with_preset_library(mut self, library: PresetLibrary) -> Self {
+        self.preset_library = Some(library);
+        self
+    }
+
+    pub fn with_delivery_presets(mut self, presets: DeliveryPresets) -> Self {
+        self.delivery_presets = presets;
+        self
+    }
+
+    pub fn with_preview_cache(mut self, directory: impl Into<String>) -> Self {
+        self.preview_directory = Some(directory.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    pub fn with_input_length_policy(mut self, policy: InputLengthPolicy) -> Self {
+        self.input_length_policy = policy;
+        self
+    }
+
+    pub fn with_auto_seed(mut self, enabled: bool) -> Self {
+        self.auto_seed = enabled;
+        self
+    }
+
+    // requests that fail with a connectivity error after retries are persisted and
+    // reported as TtsError::Queued; the queue is flushed after the next successful request
+    pub fn with_offline_queue(mut self, queue: OfflineQueue) -> Self {
+        self.offline_queue = Some(queue);
+        self
+    }
+
+    pub fn with_sandbox(mut self, sandbox: Sandbox) -> Self {
+        self.sandbox = Some(sandbox);
+        self
+    }
+
+    // events are sent without blocking; a dropped receiver just stops delivery
+    pub fn with_event_sender(mut self, sender: UnboundedSender<RequestEvent>) -> Self {
+        self.event_sender = Some(sender);
+        self
+    }
+
+    pub fn with_corpus_exporter(mut self, exporter: CorpusExporter) -> Self {
+        self.corpus_exporter = Some(exporter);
+        self
+    }
+
+    pub fn with_response_cache(mut self, capacity: usize) -> Self {
+        self.response_cache = Some(ResponseCache::new(capacity));
+        self
+    }
+
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.response_cache.as_ref().map(|cache| cache.stats())
+    }
+
+    pub fn failover_stats(&self) -> Option<FailoverStats> {
+        self.failover
+            .as_ref()
+            .map(|failover| failover.stats(&self.base_url))
+    }
+
+    pub fn clear_cache(&self) {
+        if let Some(ref cache) = self.response_cache {
+            cache.clear();
+        }
+    }
+
+    pub fn account_capabilities(&self) -> Option<&AccountCapabilities> {
+        self.capabilities.as_ref()
+    }
+
+    // the period's remaining characters: the capabilities' usage plus what this client has
+    // billed since. None without capabilities or for plans with no character limit.
+    pub fn remaining_characters(&self) -> Option<u64> {
+        let subscription = &self.capabilities.as_ref()?.subscription;
+        if subscription.character_limit == 0 {
+            return None;
+        }
+        let used = subscription.character_count + self.characters_used.load(Ordering::Relaxed);
+        Some(subscription.character_limit.saturating_sub(used))
+    }
+
+    pub async fn fetch_account_capabilities(&self) -> Result<AccountCapabilities, TtsError> {
+        let models: Vec<ModelInfo> = self.get_json("/v1/models").await?;
+        let subscription: Subscription = self.get_json("/v1/user/subscription").await?;
+        Ok(AccountCapabilities {
+            models,
+            subscription,
+        })
+    }
+
+    // an authenticated request that costs no credits, so the first synthesis doesn't pay for
+    // connection setup; also surfaces a bad API key early
+    pub async fn warm_up(&self) -> Result<(), TtsError> {
+        self.get_json::<serde_json::Value>("/v1/user/subscription")
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn send(&self, request: SpeechRequest) -> Result<SpeechResponse, TtsError> {
+        self.send_speech_request(request).await
+    }
+
+    // runs the checks `send` would run before contacting the API, on the text as it would be
+    // sent: after alias and preset resolution, degradation, preprocessing, and the input
+    // length policy
+    pub fn validate(&self, request: &SpeechRequest) -> Result<(), TtsError> {
+        let mut request = self.prepare(request.clone())?;
+        let max_length = self.max_input_length(&request.model);
+        if self.input_length_policy != InputLengthPolicy::Reject && request.text.len() > max_length
+        {
+            request.text = truncate_input(&request.text, max_length)?;
+        }
+        self.check_request(&request)
+    }
+
     pub fn synthesize(&self) -> SpeechRequestBuilder {
         SpeechRequestBuilder {
             client: self,
@@ -43,16 +372,748 @@ impl SpeechClient {
         }
     }
 
-    async fn send_speech_request(
+    pub async fn replay(&self, bundle: &ReproBundle) -> Result<ReplayReport, TtsError> {
+        if self.api_key.is_empty() {
+            return Err(TtsError::MissingApiKey);
+        }
+
+        let body = serde_json::to_vec(&bundle.request)
+            .map_err(|e| TtsError::SerializationError(e.to_string()))?;
+        let raw = self
+            .post_speech(
+                &bundle.voice_id,
+                false,
+                bundle.output_format.as_str(),
+                &bundle.query_params,
+                body,
+            )
+            .await?;
+
+        if !(200..300).contains(&raw.status) {
+            return Err(api_error(raw.status, &raw.headers, &raw.body));
+        }
+
+        let audio_sha256 = sha256_hex(&raw.body);
+        Ok(ReplayReport {
+            matches: audio_sha256 == bundle.audio_sha256,
+            audio_sha256,
+            audio_data: raw.body,
+        })
+    }
+
+    pub async fn compare(
+        &self,
+        text: impl Into<String>,
+        variants: Vec<impl Into<ComparisonVariant>>,
+    ) -> Vec<ComparisonResult> {
+        let template = SpeechRequest {
+            text: text.into(),
+            ..SpeechRequest::default()
+        };
+        self.compare_request(&template, variants).await
+    }
+
+    // runs every variant concurrently; results keep the order of `variants`
+    pub async fn compare_request(
+        &self,
+        template: &SpeechRequest,
+        variants: Vec<impl Into<ComparisonVariant>>,
+    ) -> Vec<ComparisonResult> {
+        let variants: Vec<ComparisonVariant> = variants.into_iter().map(Into::into).collect();
+        let results = join_all(
+            variants
+                .iter()
+                .map(|variant| self.send_speech_request(variant.apply(template))),
+        )
+        .await;
+
+        variants
+            .into_iter()
+            .zip(results)
+            .map(|(variant, result)| ComparisonResult {
+                label: variant.label,
+                result,
+            })
+            .collect()
+    }
+
+    // the cache key covers voice, model, and sample text, so changing any of them re-synthesizes
+    pub async fn preview(&self, voice: &Voice) -> Result<Vec<u8>, TtsError> {
+        let request = SpeechRequest {
+            text: PREVIEW_TEXT.to_string(),
+            voice: voice.clone(),
+            ..SpeechRequest::default()
+        };
+        let Some(ref directory) = self.preview_directory else {
+            return Ok(self.send_speech_request(request).await?.audio_data);
+        };
+
+        let key = sha256_hex(format!("{}:{}", request.model.as_str(), PREVIEW_TEXT).as_bytes());
+        let path = format!(
+            "{}/{}-{}.{}",
+            directory,
+            voice.as_voice_id(),
+            &key[..16],
+            AudioFormat::default().extension()
+        );
+        if let Ok(file) = vfs::open_file(&path, false, None) {
+            if let Ok(audio) = file.read() {
+                return Ok(audio);
+            }
+        }
+
+        let audio = self.send_speech_request(request).await?.audio_data;
+        vfs::open_dir(directory, true, None).map_err(|e| TtsError::Vfs(e.to_string()))?;
+        write_file(&path, &audio)?;
+        Ok(audio)
+    }
+
+    pub fn session(&self) -> SpeechSession {
+        SpeechSession::new(self)
+    }
+
+    pub fn audiobook(&self, directory: impl Into<String>) -> AudiobookBuilder {
+        AudiobookBuilder::new(self, directory.into())
+    }
+
+    pub fn document(&self, directory: impl Into<String>) -> DocumentBuilder {
+        DocumentBuilder::new(self, directory.into())
+    }
+
+    pub fn sweep(&self, directory: impl Into<String>) -> SweepBuilder {
+        SweepBuilder::new(self, directory.into())
+    }
+
+    // `directory` is the one the document was rendered into
+    pub async fn patch_document(
+        &self,
+        directory: &str,
+        segment: usize,
+        text: impl Into<String>,
+    ) -> Result<DocumentManifest, TtsError> {
+        patch_segment(self, directory, segment, text.into()).await
+    }
+
+    // synthesizes in the best PCM format the account allows and yields 20 ms frames of
+    // 48 kHz stereo-interleaved samples; any output_format on the request is replaced
+    pub async fn voice_chat_stream(
+        &self,
+        mut request: SpeechRequest,
+    ) -> Result<impl Stream<Item = Vec<i16>>, TtsError> {
+        let format = best_pcm_format(self.capabilities.as_ref());
+        request.output_format = Some(format.clone());
+        let response = self.send_speech_request(request).await?;
+        Ok(stream::iter(voice_chat_frames(
+            &response.audio_data,
+            &format,
+        )))
+    }
+
+    pub(crate) fn resolve_voice(&self, request: SpeechRequest) -> Result<SpeechRequest, TtsError> {
+        let request = self.resolve_request_preset(request)?;
+        let request = self.resolve_voice_alias(request)?;
+        let request = self.resolve_delivery(request)?;
+        self.resolve_voice_preset(request)
+    }
+
+    // runs first, so aliases, deliveries, and voice presets named by the preset resolve too
+    fn resolve_request_preset(
+        &self,
+        mut request: SpeechRequest,
+    ) -> Result<SpeechRequest, TtsError> {
+        let Some(name) = request.preset.take() else {
+            return Ok(request);
+        };
+        let preset = match &self.preset_library {
+            Some(library) => library.get(&name)?,
+            None => return Err(TtsError::UnknownRequestPreset(name)),
+        };
+        Ok(preset.apply(request))
+    }
+
+    fn resolve_voice_alias(&self, mut request: SpeechRequest) -> Result<SpeechRequest, TtsError> {
+        let Some(alias) = request.voice_alias.take() else {
+            return Ok(request);
+        };
+        request.voice = self
+            .voice_aliases
+            .as_ref()
+            .and_then(|aliases| aliases.get(&alias))
+            .ok_or(TtsError::UnknownVoiceAlias(alias))?;
+        Ok(request)
+    }
+
+    // explicit settings win over the delivery preset, which wins over the voice's preset
+    fn resolve_delivery(&self, mut request: SpeechRequest) -> Result<SpeechRequest, TtsError> {
+        let Some(name) = request.delivery.take() else {
+            return Ok(request);
+        };
+        let delivery = self
+            .delivery_presets
+            .get(&name, &request.model)
+            .ok_or(TtsError::UnknownDelivery(name))?;
+        request.voice_settings = Some(merge_voice_settings(request.voice_settings, delivery));
+        Ok(request)
+    }
+
+    // fields set explicitly on the request take precedence over the preset
+    fn resolve_voice_preset(&self, mut request: SpeechRequest) -> Result<SpeechRequest, TtsError> {
+        let Some(name) = request.voice_preset.take() else {
+            return Ok(request);
+        };
+        let preset = self
+            .voice_presets
+            .as_ref()
+            .and_then(|presets| presets.get(&request.voice, &name))
+            .ok_or_else(|| TtsError::PresetNotFound {
+                voice: request.voice.name().to_string(),
+                name,
+            })?;
+
+        request.voice_settings = Some(merge_voice_settings(request.voice_settings, preset));
+        Ok(request)
+    }
+
+    // Flash and Turbo models reject forced normalization outside Enterprise plans; rather than
+    // let the API fail with a 400, fall back to Auto and report it
+    pub(crate) fn adjust_text_normalization(
+        &self,
+        mut request: SpeechRequest,
+    ) -> (SpeechRequest, Option<String>) {
+        let restricted = matches!(
+            request.model,
+            TtsModel::ElevenFlashV25 | TtsModel::ElevenTurboV25
+        );
+        let enterprise = self
+            .capabilities
+            .as_ref()
+            .is_some_and(|c| c.subscription.tier.starts_with("enterprise"));
+        let forced = matches!(
+            request.apply_text_normalization,
+            Some(TextNormalization::On)
+        );
+        if !restricted || !forced || enterprise {
+            return (request, None);
+        }
+
+        request.apply_text_normalization = Some(TextNormalization::Auto);
+        let warning = format!(
+            "apply_text_normalization \"on\" is not supported for {}; used \"auto\"",
+            request.model.as_str()
+        );
+        (request, Some(warning))
+    }
+
+    // the seed is fixed after the cache lookup, so an auto seed never keys the cache, and
+    // before any retry, hedge, or fallback, so every attempt shares it
+    pub(crate) fn assign_seed(&self, mut request: SpeechRequest) -> SpeechRequest {
+        if self.auto_seed && request.seed.is_none() {
+            request.seed = Some(rand::random());
+        }
+        request
+    }
+
+    pub(crate) fn preprocess(&self, request: SpeechRequest) -> SpeechRequest {
+        let mut request = apply_ssml(request);
+        if self.pii_redactor.is_none()
+            && self.pronunciation_rules.is_none()
+            && !self.verbalize_numbers
+        {
+            return request;
+        }
+
+        let language = request.language_code.clone();
+        // redaction runs first, so pronunciation rules and verbalization never see the PII
+        let transform = |mut text: String| -> String {
+            if let Some(ref redactor) = self.pii_redactor {
+                text = redactor.redact(&text);
+            }
+            if let Some(ref rules) = self.pronunciation_rules {
+                text = rules.apply(&text, language.as_deref());
+            }
+            if self.verbalize_numbers {
+                text = verbalize(&text, language.as_deref());
+            }
+            text
+        };
+
+        request.text = transform(request.text);
+        request.previous_text = request.previous_text.map(transform);
+        request.next_text = request.next_text.map(transform);
+        request
+    }
+
+    // runs after preprocessing, so text introduced by pronunciation rules is filtered too
+    pub(crate) fn filter_profanity(
+        &self,
+        mut request: SpeechRequest,
+    ) -> Result<SpeechRequest, TtsError> {
+        let Some(ref filter) = self.profanity_filter else {
+            return Ok(request);
+        };
+        request.text = filter.apply(&request.text)?;
+        request.previous_text = request
+            .previous_text
+            .map(|text| filter.apply(&text))
+            .transpose()?;
+        request.next_text = request
+            .next_text
+            .map(|text| filter.apply(&text))
+            .transpose()?;
+        Ok(request)
+    }
+
+    pub(crate) async fn send_speech_request(
+        &self,
+        mut request: SpeechRequest,
+    ) -> Result<SpeechResponse, TtsError> {
+        if self.event_sender.is_some() && request.correlation_id.is_none() {
+            let n = self.event_counter.fetch_add(1, Ordering::Relaxed) + 1;
+            request.correlation_id = Some(format!("req-{}", n));
+        }
+        let correlation_id = request.correlation_id.clone();
+        let started = Instant::now();
+        self.emit(correlation_id.as_ref(), |correlation_id| {
+            RequestEvent::Queued {
+                correlation_id,
+                text_length: request.text.len(),
+            }
+        });
+
+        let result = self.process_speech_request(request).await;
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        self.emit(correlation_id.as_ref(), |correlation_id| match &result {
+            Ok(response) => RequestEvent::Completed {
+                correlation_id,
+                bytes: response.audio_data.len(),
+                request_id: response.request_id.clone(),
+                elapsed_ms,
+            },
+            Err(e) => RequestEvent::Failed {
+                correlation_id,
+                error: e.to_string(),
+                elapsed_ms,
+            },
+        });
+        result
+    }
+
+    fn emit(&self, correlation_id: Option<&String>, event: impl FnOnce(String) -> RequestEvent) {
+        if let (Some(sender), Some(correlation_id)) = (&self.event_sender, correlation_id) {
+            let _ = sender.unbounded_send(event(correlation_id.clone()));
+        }
+    }
+
+    // what every request goes through before the input length policy: alias and preset
+    // resolution, degradation, preprocessing, and the profanity filter
+    pub(crate) fn prepare(&self, request: SpeechRequest) -> Result<SpeechRequest, TtsError> {
+        let request = self.degrade(self.resolve_voice(request)?);
+        self.filter_profanity(self.preprocess(request))
+    }
+
+    async fn process_speech_request(
+        &self,
+        request: SpeechRequest,
+    ) -> Result<SpeechResponse, TtsError> {
+        let request = self.prepare(request)?;
+
+        let Some(ref queue) = self.offline_queue else {
+            return self.send_prepared(request).await;
+        };
+        match self.send_prepared(request.clone()).await {
+            Err(TtsError::HttpClient(_)) => Err(TtsError::Queued(queue.enqueue(&request)?)),
+            Ok(response) => {
+                // every outcome is persisted with the queue, so the result isn't needed here;
+                // the batch is bounded so a success waits on at most a few replays
+                let _ = self.flush_queue_entries(OFFLINE_FLUSH_BATCH).await;
+                Ok(response)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    // applies the degradation policy once remaining quota is below its threshold
+    fn degrade(&self, request: SpeechRequest) -> SpeechRequest {
+        let (Some(policy), Some(capabilities)) = (&self.degradation_policy, &self.capabilities)
+        else {
+            return request;
+        };
+        let limit = capabilities.subscription.character_limit;
+        match self.remaining_characters() {
+            Some(remaining) if policy.applies(remaining, limit) => policy.apply(request),
+            _ => request,
+        }
+    }
+
+    // replays queued requests in order, stopping at the first that fails transiently
+    pub async fn flush_offline_queue(&self) -> Result<Vec<FlushedRequest>, TtsError> {
+        self.flush_queue_entries(usize::MAX).await
+    }
+
+    async fn flush_queue_entries(&self, limit: usize) -> Result<Vec<FlushedRequest>, TtsError> {
+        let Some(ref queue) = self.offline_queue else {
+            return Ok(Vec::new());
+        };
+        if !queue.begin_flush() {
+            return Ok(Vec::new());
+        }
+        let result = self.flush_queue(queue, limit).await;
+        queue.end_flush();
+        result
+    }
+
+    // an entry leaves the queue once its audio is written or the API rejects it with a 4xx;
+    // transient errors stop the flush and anything else, such as a failed VFS write, is
+    // returned, both keeping the entry for the next flush
+    async fn flush_queue(
+        &self,
+        queue: &OfflineQueue,
+        limit: usize,
+    ) -> Result<Vec<FlushedRequest>, TtsError> {
+        let mut flushed = Vec::new();
+        for queued in queue.pending()?.into_iter().take(limit) {
+            let result = match self.send_prepared(queued.to_request()).await {
+                Ok(response) => Ok(queue.complete(&queued, &response)?),
+                Err(e) if RetryClass::of(&e).is_some() => break,
+                Err(e) if e.is_client_error() => {
+                    queue.fail(&queued, &e)?;
+                    Err(e)
+                }
+                Err(e) => return Err(e),
+            };
+            // re-read so requests queued during the flush are kept
+            let mut pending = queue.pending()?;
+            pending.retain(|p| p.id != queued.id);
+            queue.save(&pending)?;
+            flushed.push(FlushedRequest {
+                id: queued.id,
+                result,
+            });
+        }
+        Ok(flushed)
+    }
+
+    // applies the input length policy to a request that is ready to send
+    pub(crate) async fn send_prepared(
+        &self,
+        mut request: SpeechRequest,
+    ) -> Result<SpeechResponse, TtsError> {
+        let max_length = self.max_input_length(&request.model);
+        if request.text.len() > max_length {
+            match self.input_length_policy {
+                InputLengthPolicy::Reject => {}
+                InputLengthPolicy::Truncate => {
+                    let original_length = request.text.len();
+                    request.text = truncate_input(&request.text, max_length)?;
+                    let warning = format!(
+                        "input truncated at a sentence boundary from {} to {} characters",
+                        original_length,
+                        request.text.len()
+                    );
+                    let mut response = self.send_preprocessed(request).await?;
+                    response.warnings.push(warning);
+                    return Ok(response);
+                }
+                InputLengthPolicy::Chunk => return self.send_chunked(request, max_length).await,
+            }
+        }
+
+        self.send_preprocessed(request).await
+    }
+
+    // the API limit, lowered to the plan's per-request limit when capabilities are known
+    pub(crate) fn max_input_length(&self, model: &TtsModel) -> usize {
+        self.capabilities
+            .as_ref()
+            .and_then(|capabilities| capabilities.max_characters(model.as_str()))
+            .map_or(MAX_INPUT_LENGTH, |limit| limit.min(MAX_INPUT_LENGTH))
+    }
+
+    async fn send_preprocessed(&self, request: SpeechRequest) -> Result<SpeechResponse, TtsError> {
+        let cache = self
+            .response_cache
+            .as_ref()
+            .map(|cache| (cache, cache_key(&request)));
+        if let Some((cache, ref key)) = cache {
+            if let Some(mut response) = cache.get(key) {
+                response.tags = request.tags;
+                return Ok(response);
+            }
+        }
+
+        let mut request = self.assign_seed(request);
+        let response = match request.hedge.take() {
+            Some(hedge) if hedge.model != request.model => self.send_hedged(request, hedge).await?,
+            _ => self.send_with_voice_fallback(request).await?,
+        };
+        if let Some((cache, key)) = cache {
+            cache.insert(key, response.clone());
+        }
+        Ok(response)
+    }
+
+    // synthesizes each chunk with request stitching and joins the audio; ID3 tags are
+    // embedded once into the joined audio rather than into every chunk
+    async fn send_chunked(
+        &self,
+        request: SpeechRequest,
+        max_length: usize,
+    ) -> Result<SpeechResponse, TtsError> {
+        // the chunks are checked one by one, without their tags, so the tags' format
+        // requirement is checked here
+        check_id3_format(&request)?;
+        let chunks = chunk_text(&request.text, max_length);
+        let mut template = request.clone();
+        template.id3_tags = None;
+
+        let correlation_id = request.correlation_id.as_ref();
+        let chunk_count = chunks.len();
+        let stitched = send_stitched(&template, &chunks, |i, chunk_request| async move {
+            let response = self.send_preprocessed(chunk_request).await?;
+            self.emit(correlation_id, |correlation_id| {
+                RequestEvent::ChunkReceived {
+                    correlation_id,
+                    chunk: i + 1,
+                    chunks: chunk_count,
+                    bytes: response.audio_data.len(),
+                }
+            });
+            Ok(response)
+        })
+        .await?;
+
+        let mut audio = stitched.audio;
+        let mut response = stitched.last;
+        if let Some(mut tags) = request.id3_tags {
+            if tags.artist.is_none() {
+                tags.artist = Some(response.voice.name().to_string());
+            }
+            audio = tags.embed(&audio);
+        }
+        response.audio_sha256 = sha256_hex(&audio);
+        response.audio_data = audio;
+        response.repro_bundle = None;
+        response.alignment = None;
+        response.normalized_alignment = None;
+        response
+            .warnings
+            .push(format!("input split into {} requests", chunks.len()));
+        Ok(response)
+    }
+
+    async fn send_hedged(
         &self,
         request: SpeechRequest,
+        hedge: HedgeConfig,
     ) -> Result<SpeechResponse, TtsError> {
+        let mut hedged_request = request.clone();
+        hedged_request.model = hedge.model;
+
+        let primary = pin!(self.send_with_voice_fallback(request));
+        let hedged = pin!(async {
+            let _ = sleep(hedge.delay_ms).await;
+            self.send_with_voice_fallback(hedged_request).await
+        });
+
+        // the losing request is dropped; its response is discarded when it arrives
+        match select(primary, hedged).await {
+            Either::Left((Ok(response), _)) => Ok(response),
+            Either::Left((Err(_), hedged)) => hedged.await,
+            Either::Right((Ok(response), _)) => Ok(response),
+            Either::Right((Err(_), primary)) => primary.await,
+        }
+    }
+
+    async fn send_with_voice_fallback(
+        &self,
+        request: SpeechRequest,
+    ) -> Result<SpeechResponse, TtsError> {
+        let fallback_voice = self
+            .fallback_voice
+            .as_ref()
+            .filter(|voice| **voice != request.voice);
+        let Some(fallback_voice) = fallback_voice else {
+            return self.send_with_model_fallback(request).await;
+        };
+
+        match self.send_with_model_fallback(request.clone()).await {
+            Err(error) if error.is_voice_not_found() => {
+                let mut request = request;
+                request.voice = fallback_voice.clone();
+                self.send_with_model_fallback(request).await
+            }
+            result => result,
+        }
+    }
+
+    async fn send_with_model_fallback(
+        &self,
+        request: SpeechRequest,
+    ) -> Result<SpeechResponse, TtsError> {
+        let fallback_model = self
+            .fallback_model
+            .as_ref()
+            .filter(|model| **model != request.model);
+        let Some(fallback_model) = fallback_model else {
+            return self.send_with_retries(request).await;
+        };
+
+        match self.send_with_retries(request.clone()).await {
+            Err(error) if error.is_model_unavailable() => {
+                let mut request = request;
+                request.model = fallback_model.clone();
+                self.send_with_retries(request).await
+            }
+            result => result,
+        }
+    }
+
+    async fn send_with_retries(&self, request: SpeechRequest) -> Result<SpeechResponse, TtsError> {
+        // retries are counted per failure class, each against its own limit
+        let mut retries: HashMap<Option<RetryClass>, u32> = HashMap::new();
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            self.emit(request.correlation_id.as_ref(), |correlation_id| {
+                RequestEvent::Started {
+                    correlation_id,
+                    attempt,
+                    model: request.model.as_str().to_string(),
+                    voice_id: request.voice.as_voice_id().to_string(),
+                }
+            });
+            let error = match self.send_speech_request_once(request.clone()).await {
+                Ok(response) => return Ok(response),
+                Err(error) => error,
+            };
+            let retry = retries.entry(RetryClass::of(&error)).or_default();
+            let Some(delay_ms) = self.retry_policy.retry_delay_ms(&error, *retry) else {
+                return Err(error);
+            };
+            self.emit(request.correlation_id.as_ref(), |correlation_id| {
+                RequestEvent::Retried {
+                    correlation_id,
+                    attempt,
+                    delay_ms,
+                    error: error.to_string(),
+                }
+            });
+            let _ = sleep(delay_ms).await;
+            *retry += 1;
+        }
+    }
+
+    async fn send_speech_request_once(
+        &self,
+        request: SpeechRequest,
+    ) -> Result<SpeechResponse, TtsError> {
+        let (request, warning) = self.adjust_text_normalization(request);
+        let raw = self.send_raw_request(&request).await?;
+
+        let status = raw.status;
+        let request_id = raw
+            .headers
+            .get("request-id")
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+        let mut content_type = raw
+            .headers
+            .get("content-type")
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+        let mut body = raw.body;
+
+        if (200..300).contains(&status) {
+            if self.sandbox.is_none() {
+                let billed = billable_characters(&request.text, &request.model);
+                self.characters_used.fetch_add(billed, Ordering::Relaxed);
+            }
+            let mut alignment = None;
+            let mut normalized_alignment = None;
+            if request.with_timestamps {
+                let timestamps: TimestampsResponse = serde_json::from_slice(&body)
+                    .map_err(|e| TtsError::DeserializationError(e.to_string()))?;
+                body = BASE64
+                    .decode(timestamps.audio_base64)
+                    .map_err(|e| TtsError::DeserializationError(e.to_string()))?;
+                alignment = timestamps.alignment;
+                normalized_alignment = timestamps.normalized_alignment;
+                // the content type describes the JSON envelope, not the decoded audio
+                content_type = None;
+            }
+
+            let exported_request = self
+                .corpus_exporter
+                .as_ref()
+                .map(|_| SpeechRequestJson::from(request.clone()));
+            let repro_bundle = match request.seed {
+                Some(seed) => Some(ReproBundle::capture(&request, seed, &body)?),
+                None => None,
+            };
+
+            let format = request.output_format.unwrap_or_default();
+            if self.verify_content && !is_audio_content(&format, content_type.as_deref(), &body) {
+                return Err(TtsError::UnexpectedContent {
+                    expected: format.as_str().to_string(),
+                    content_type,
+                });
+            }
+            if format.estimated_duration_ms(body.len()) < MIN_AUDIO_DURATION_MS {
+                return Err(TtsError::EmptyAudio {
+                    size: body.len(),
+                    text_length: request.text.len(),
+                });
+            }
+            // only audio that passed the checks above is worth reproducing
+            if let (Some(bundle), Some(directory)) = (&repro_bundle, &self.repro_directory) {
+                bundle.save(&format!("{}/{}.json", directory, bundle.audio_sha256))?;
+            }
+            let audio_data = match request.id3_tags {
+                Some(mut tags) => {
+                    if tags.artist.is_none() {
+                        tags.artist = Some(request.voice.name().to_string());
+                    }
+                    if tags.comment.is_none() {
+                        tags.comment = request_id.clone();
+                    }
+                    tags.embed(&body)
+                }
+                None => body,
+            };
+            let response = SpeechResponse {
+                audio_sha256: sha256_hex(&audio_data),
+                audio_data,
+                format,
+                model: request.model,
+                voice: request.voice,
+                request_id,
+                repro_bundle,
+                tags: request.tags,
+                warnings: warning.into_iter().collect(),
+                alignment,
+                normalized_alignment,
+            };
+            if let (Some(exporter), Some(exported_request)) =
+                (&self.corpus_exporter, &exported_request)
+            {
+                exporter.export(exported_request, &response)?;
+            }
+            Ok(response)
+        } else {
+            Err(api_error(status, &raw.headers, &body))
+        }
+    }
+
+    fn check_request(&self, request: &SpeechRequest) -> Result<(), TtsError> {
         if request.text.is_empty() {
             return Err(TtsError::MissingInput);
         }
 
         if request.text.len() > MAX_INPUT_LENGTH {
-            return Err(TtsError::InputTooLong(request.text.len()));
+            return Err(TtsError::InputTooLong {
+                length: request.text.len(),
+                max: MAX_INPUT_LENGTH,
+            });
         }
 
         if let Some(ref settings) = request.voice_settings {
@@ -80,6 +1141,36 @@ impl SpeechClient {
                     });
                 }
             }
+            if let Some(speed) = settings.speed {
+                if !(MIN_SPEED..=MAX_SPEED).contains(&speed) {
+                    return Err(TtsError::InvalidSpeed(speed));
+                }
+            }
+        }
+
+        if let Voice::Custom(ref id) = request.voice {
+            if id.len() != VOICE_ID_LENGTH || !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(TtsError::InvalidVoiceId(id.clone()));
+            }
+        }
+
+        check_id3_format(request)?;
+
+        if let Some(ref capabilities) = self.capabilities {
+            capabilities.check(request)?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) async fn send_raw_request(
+        &self,
+        request: &SpeechRequest,
+    ) -> Result<RawResponse, TtsError> {
+        self.check_request(request)?;
+
+        if let Some(ref sandbox) = self.sandbox {
+            return sandbox.respond(request);
         }
 
         if self.api_key.is_empty() {
@@ -91,10 +1182,6 @@ impl SpeechClient {
         let body = serde_json::to_vec(&json_request)
             .map_err(|e| TtsError::SerializationError(e.to_string()))?;
 
-        let mut headers = HashMap::new();
-        headers.insert("xi-api-key".to_string(), self.api_key.clone());
-        headers.insert("Content-Type".to_string(), "application/json".to_string());
-
         let voice_id = request.voice.as_voice_id();
         let default_format = AudioFormat::default();
         let output_format = request
@@ -103,10 +1190,93 @@ impl SpeechClient {
             .unwrap_or(&default_format)
             .as_str();
 
-        let url = url::Url::parse(&format!(
-            "{}/v1/text-to-speech/{}?output_format={}",
-            self.base_url,
+        self.post_speech(
+            voice_id,
+            request.with_timestamps,
+            output_format,
+            &request.query_params,
+            body,
+        )
+        .await
+    }
+
+    fn request_headers(&self) -> HashMap<String, String> {
+        let mut headers = self.default_headers.clone();
+        if let Some(ref user_agent) = self.user_agent {
+            headers.insert("User-Agent".to_string(), user_agent.clone());
+        }
+        headers.insert("xi-api-key".to_string(), self.api_key.clone());
+        headers.insert("Content-Type".to_string(), "application/json".to_string());
+        headers
+    }
+
+    fn active_base_url(&self) -> &str {
+        match self.failover {
+            Some(ref failover) => failover.base_url(&self.base_url),
+            None => &self.base_url,
+        }
+    }
+
+    fn record_connectivity(&self, base_url: &str, connected: bool) {
+        let Some(ref failover) = self.failover else {
+            return;
+        };
+        if connected {
+            failover.record_success(base_url, &self.base_url);
+        } else {
+            failover.record_connectivity_error(base_url, &self.base_url);
+        }
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T, TtsError> {
+        if self.api_key.is_empty() {
+            return Err(TtsError::MissingApiKey);
+        }
+
+        let base_url = self.active_base_url().to_string();
+        let url = url::Url::parse(&format!("{}{}", base_url, path))
+            .map_err(|e| TtsError::HttpClient(HttpClientError::BadUrl { url: e.to_string() }))?;
+
+        let response = send_request_await_response(
+            Method::GET,
+            url,
+            Some(self.request_headers()),
+            self.timeout,
+            Vec::new(),
+        )
+        .await;
+        self.record_connectivity(&base_url, response.is_ok());
+        let response = response.map_err(TtsError::HttpClient)?;
+
+        let status = response.status().as_u16();
+        if !(200..300).contains(&status) {
+            return Err(api_error(status, response.headers(), response.body()));
+        }
+        serde_json::from_slice(response.body())
+            .map_err(|e| TtsError::DeserializationError(e.to_string()))
+    }
+
+    async fn post_speech(
+        &self,
+        voice_id: &str,
+        with_timestamps: bool,
+        output_format: &str,
+        query_params: &[(String, String)],
+        body: Vec<u8>,
+    ) -> Result<RawResponse, TtsError> {
+        let headers = self.request_headers();
+        let base_url = self.active_base_url().to_string();
+
+        let endpoint = if with_timestamps {
+            "/with-timestamps"
+        } else {
+            ""
+        };
+        let mut url = url::Url::parse(&format!(
+            "{}/v1/text-to-speech/{}{}?output_format={}",
+            base_url,
             voice_id,
+            endpoint,
             output_format
         ))
         .map_err(|e| {
@@ -114,6 +1284,9 @@ impl SpeechClient {
                 url: e.to_string(),
             })
         })?;
+        for (key, value) in query_params {
+            url.query_pairs_mut().append_pair(key, value);
+        }
 
         let response = send_request_await_response(
             Method::POST,
@@ -122,35 +1295,173 @@ impl SpeechClient {
             self.timeout,
             body,
         )
-        .await
-        .map_err(TtsError::HttpClient)?;
+        .await;
+        self.record_connectivity(&base_url, response.is_ok());
+        let response = response.map_err(TtsError::HttpClient)?;
 
-        let status = response.status();
-        let body = response.into_body();
+        let status = response.status().as_u16();
+        let headers = response.headers().clone();
 
-        if status.is_success() {
-            let format = request.output_format.unwrap_or_default();
-            Ok(SpeechResponse {
-                audio_data: body,
-                format,
-            })
-        } else {
-            if let Ok(error_response) = serde_json::from_slice::<ApiErrorResponse>(&body) {
-                Err(TtsError::ApiError {
-                    status: status.as_u16(),
-                    message: error_response.error.message,
-                })
-            } else {
-                let message = String::from_utf8_lossy(&body).to_string();
-                Err(TtsError::ApiError {
-                    status: status.as_u16(),
-                    message,
-                })
+        if let Some(limit) = self.max_response_size {
+            let content_length = response
+                .headers()
+                .get("content-length")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<usize>().ok());
+            let size = content_length.unwrap_or(0).max(response.body().len());
+            if size > limit {
+                return Err(TtsError::ResponseTooLarge { size, limit });
             }
         }
+
+        Ok(RawResponse {
+            status,
+            headers,
+            body: response.into_body(),
+        })
+    }
+}
+
+fn api_error(status: u16, headers: &http::HeaderMap, body: &[u8]) -> TtsError {
+    let (code, message) = parse_api_error(body);
+    let retry_after_ms = headers
+        .get("retry-after")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(|seconds| seconds.saturating_mul(1000));
+
+    // quota_exceeded is also reported as 401, but it is not a credential problem
+    match status {
+        401 if code.as_deref() != Some("quota_exceeded") => TtsError::InvalidApiKey,
+        403 => TtsError::Forbidden { reason: message },
+        _ => TtsError::ApiError {
+            status,
+            code,
+            message,
+            retry_after_ms,
+        },
     }
 }
 
+fn parse_api_error(body: &[u8]) -> (Option<String>, String) {
+    if let Ok(error_response) = serde_json::from_slice::<DetailErrorResponse>(body) {
+        (error_response.detail.status, error_response.detail.message)
+    } else if let Ok(error_response) = serde_json::from_slice::<ApiErrorResponse>(body) {
+        (error_response.error.code, error_response.error.message)
+    } else {
+        (None, String::from_utf8_lossy(body).to_string())
+    }
+}
+
+pub(crate) struct Stitched {
+    pub audio: Vec<u8>,
+    pub request_ids: Vec<String>,
+    // the response to the last chunk
+    pub last: SpeechResponse,
+}
+
+// sends each chunk as a copy of `template`, stitched to the chunks before it by their request
+// ids, or by their text until the API has returned any ids, and joins the audio
+pub(crate) async fn send_stitched<F, Fut>(
+    template: &SpeechRequest,
+    chunks: &[String],
+    mut send: F,
+) -> Result<Stitched, TtsError>
+where
+    F: FnMut(usize, SpeechRequest) -> Fut,
+    Fut: Future<Output = Result<SpeechResponse, TtsError>>,
+{
+    let mut audio = Vec::new();
+    let mut request_ids: Vec<String> = Vec::new();
+    let mut last = None;
+
+    for (i, chunk) in chunks.iter().enumerate() {
+        let mut request = template.clone();
+        request.text = chunk.clone();
+        if !request_ids.is_empty() {
+            let start = request_ids.len().saturating_sub(MAX_STITCHED_REQUEST_IDS);
+            request.previous_request_ids = Some(request_ids[start..].to_vec());
+        } else if i > 0 {
+            request.previous_text = Some(chunks[i - 1].clone());
+        }
+        if let Some(next) = chunks.get(i + 1) {
+            request.next_text = Some(next.clone());
+        }
+
+        let response = send(i, request).await?;
+        audio.extend_from_slice(&response.audio_data);
+        if let Some(ref request_id) = response.request_id {
+            request_ids.push(request_id.clone());
+        }
+        last = Some(response);
+    }
+
+    let last = last.ok_or(TtsError::MissingInput)?;
+    Ok(Stitched {
+        audio,
+        request_ids,
+        last,
+    })
+}
+
+fn check_id3_format(request: &SpeechRequest) -> Result<(), TtsError> {
+    if request.id3_tags.is_some() {
+        let format = request.output_format.clone().unwrap_or_default();
+        if !format.is_mp3() {
+            return Err(TtsError::Id3UnsupportedFormat(format.as_str().to_string()));
+        }
+    }
+    Ok(())
+}
+
+// the text up to the sentence boundary nearest the limit; text that is only whitespace has
+// nothing to send
+fn truncate_input(text: &str, max_length: usize) -> Result<String, TtsError> {
+    chunk_text(text, max_length)
+        .into_iter()
+        .next()
+        .ok_or(TtsError::MissingInput)
+}
+
+// fields set in `settings` win; the rest come from `fallback`
+fn merge_voice_settings(settings: Option<VoiceSettings>, fallback: VoiceSettings) -> VoiceSettings {
+    match settings {
+        Some(settings) => VoiceSettings {
+            stability: settings.stability.or(fallback.stability),
+            similarity_boost: settings.similarity_boost.or(fallback.similarity_boost),
+            style: settings.style.or(fallback.style),
+            use_speaker_boost: settings.use_speaker_boost.or(fallback.use_speaker_boost),
+            speed: settings.speed.or(fallback.speed),
+        },
+        None => fallback,
+    }
+}
+
+fn is_audio_content(format: &AudioFormat, content_type: Option<&str>, body: &[u8]) -> bool {
+    if let Some(content_type) = content_type {
+        let content_type = content_type.to_ascii_lowercase();
+        if content_type.starts_with("text/") || content_type.contains("json") {
+            return false;
+        }
+    }
+
+    let head = &body[..body.len().min(64)];
+    let head = String::from_utf8_lossy(head)
+        .trim_start()
+        .to_ascii_lowercase();
+    if head.starts_with("<!doctype") || head.starts_with("<html") {
+        return false;
+    }
+
+    if format.is_mp3() && body.len() >= 2 {
+        let has_id3 = body.starts_with(b"ID3");
+        let has_frame_sync = body[0] == 0xff && body[1] & 0xe0 == 0xe0;
+        return has_id3 || has_frame_sync;
+    }
+
+    true
+}
+
 pub struct SpeechRequestBuilder<'a> {
     client: &'a SpeechClient,
     request: SpeechRequest,
@@ -166,6 +1477,16 @@ impl<'a> SpeechRequestBuilder<'a> {
         self.text(text)
     }
 
+    pub fn html(self, html: &str) -> Self {
+        self.text(html_to_speech_text(html))
+    }
+
+    // translated when the request is sent, once the model is known
+    pub fn ssml(mut self, ssml: impl Into<String>) -> Self {
+        self.request.ssml = true;
+        self.text(ssml)
+    }
+
     pub fn model(mut self, model: TtsModel) -> Self {
         self.request.model = model;
         self
@@ -202,6 +1523,13 @@ impl<'a> SpeechRequestBuilder<'a> {
         self
     }
 
+    pub fn speed(mut self, speed: f32) -> Self {
+        let mut settings = self.request.voice_settings.unwrap_or_default();
+        settings.speed = Some(speed);
+        self.request.voice_settings = Some(settings);
+        self
+    }
+
     pub fn use_speaker_boost(mut self, use_speaker_boost: bool) -> Self {
         let mut settings = self.request.voice_settings.unwrap_or_default();
         settings.use_speaker_boost = Some(use_speaker_boost);
@@ -209,6 +1537,26 @@ impl<'a> SpeechRequestBuilder<'a> {
         self
     }
 
+    pub fn voice_alias(mut self, alias: impl Into<String>) -> Self {
+        self.request.voice_alias = Some(alias.into());
+        self
+    }
+
+    pub fn preset(mut self, name: impl Into<String>) -> Self {
+        self.request.preset = Some(name.into());
+        self
+    }
+
+    pub fn voice_preset(mut self, name: impl Into<String>) -> Self {
+        self.request.voice_preset = Some(name.into());
+        self
+    }
+
+    pub fn delivery(mut self, name: impl Into<String>) -> Self {
+        self.request.delivery = Some(name.into());
+        self
+    }
+
     pub fn output_format(mut self, format: AudioFormat) -> Self {
         self.request.output_format = Some(format);
         self
@@ -258,7 +1606,85 @@ impl<'a> SpeechRequestBuilder<'a> {
         self
     }
 
+    pub fn extra_field(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        self.request.extra_fields.insert(key.into(), value);
+        self
+    }
+
+    pub fn query_param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.request.query_params.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.request.tags.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn correlation_id(mut self, id: impl Into<String>) -> Self {
+        self.request.correlation_id = Some(id.into());
+        self
+    }
+
+    pub fn hedge(mut self, model: TtsModel, delay_ms: u64) -> Self {
+        self.request.hedge = Some(HedgeConfig { model, delay_ms });
+        self
+    }
+
+    pub fn with_timestamps(mut self) -> Self {
+        self.request.with_timestamps = true;
+        self
+    }
+
+    pub fn id3_tags(mut self, tags: Id3Tags) -> Self {
+        self.request.id3_tags = Some(tags);
+        self
+    }
+
+    pub fn build(self) -> SpeechRequest {
+        self.request
+    }
+
     pub async fn execute(self) -> Result<SpeechResponse, TtsError> {
         self.client.send_speech_request(self.request).await
     }
+
+    // a raw body can't be stitched, so over-long text is rejected under the Chunk policy too
+    pub async fn execute_raw(self) -> Result<RawResponse, TtsError> {
+        let mut request = self.client.assign_seed(self.client.prepare(self.request)?);
+        let max_length = self.client.max_input_length(&request.model);
+        if request.text.len() > max_length {
+            if self.client.input_length_policy != InputLengthPolicy::Truncate {
+                return Err(TtsError::InputTooLong {
+                    length: request.text.len(),
+                    max: max_length,
+                });
+            }
+            request.text = truncate_input(&request.text, max_length)?;
+        }
+        let (request, _) = self.client.adjust_text_normalization(request);
+        self.client.send_raw_request(&request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncating_whitespace_only_input_is_missing_input() {
+        assert!(matches!(
+            truncate_input("   ", 10),
+            Err(TtsError::MissingInput)
+        ));
+    }
+
+    #[test]
+    fn truncating_without_a_sentence_boundary_cuts_at_a_word() {
+        assert_eq!(
+            truncate_input("one two three four five", 10).unwrap(),
+            "one two"
+        );
+        assert_eq!(truncate_input("abcdefghijkl", 5).unwrap(), "abcde");
+    }
 }
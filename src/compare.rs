@@ -0,0 +1,55 @@
+use crate::error::TtsError;
+use crate::types::{SpeechRequest, SpeechResponse, TtsModel, Voice, VoiceSettings};
+
+#[derive(Debug, Clone)]
+pub struct ComparisonVariant {
+    pub label: String,
+    pub voice: Voice,
+    pub model: Option<TtsModel>,
+    pub voice_settings: Option<VoiceSettings>,
+}
+
+impl ComparisonVariant {
+    pub fn new(label: impl Into<String>, voice: Voice) -> Self {
+        Self {
+            label: label.into(),
+            voice,
+            model: None,
+            voice_settings: None,
+        }
+    }
+
+    pub fn model(mut self, model: TtsModel) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    pub fn voice_settings(mut self, settings: VoiceSettings) -> Self {
+        self.voice_settings = Some(settings);
+        self
+    }
+
+    pub(crate) fn apply(&self, template: &SpeechRequest) -> SpeechRequest {
+        let mut request = template.clone();
+        request.voice = self.voice.clone();
+        if let Some(ref model) = self.model {
+            request.model = model.clone();
+        }
+        if let Some(ref settings) = self.voice_settings {
+            request.voice_settings = Some(settings.clone());
+        }
+        request
+    }
+}
+
+impl From<Voice> for ComparisonVariant {
+    fn from(voice: Voice) -> Self {
+        ComparisonVariant::new(voice.name().to_string(), voice)
+    }
+}
+
+#[derive(Debug)]
+pub struct ComparisonResult {
+    pub label: String,
+    pub result: Result<SpeechResponse, TtsError>,
+}
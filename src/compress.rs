@@ -0,0 +1,202 @@
+use crate::audio::pcm_sample_rate;
+use crate::error::TtsError;
+use crate::types::AudioFormat;
+use crate::util::write_file;
+use hyperware_process_lib::vfs;
+use miniz_oxide::deflate::compress_to_vec;
+use miniz_oxide::inflate::decompress_to_vec_with_limit;
+use serde::{Deserialize, Serialize};
+
+// magic, mode byte, original length (u64 LE), then the deflate stream
+const MAGIC: &[u8; 4] = b"HWAZ";
+const HEADER_LEN: usize = 13;
+const MODE_RAW: u8 = 0;
+// 16-bit samples stored as differences from the previous sample, which deflate far better
+const MODE_PCM16_DELTA: u8 = 1;
+const LEVEL: u8 = 6;
+
+// lossless; PCM formats are delta-encoded before deflating, other formats deflated as-is
+pub fn compress_audio(audio: &[u8], format: &AudioFormat) -> Vec<u8> {
+    let pcm = pcm_sample_rate(format).is_some() && audio.len() % 2 == 0;
+    let (mode, payload) = if pcm {
+        (MODE_PCM16_DELTA, delta_encode(audio))
+    } else {
+        (MODE_RAW, audio.to_vec())
+    };
+
+    let mut out = Vec::with_capacity(HEADER_LEN + audio.len() / 2);
+    out.extend_from_slice(MAGIC);
+    out.push(mode);
+    out.extend_from_slice(&(audio.len() as u64).to_le_bytes());
+    out.extend_from_slice(&compress_to_vec(&payload, LEVEL));
+    out
+}
+
+// data without the compression header is returned unchanged, so callers can read audio
+// without knowing whether it was stored compressed
+pub fn decompress_audio(data: &[u8]) -> Result<Vec<u8>, TtsError> {
+    if !is_compressed(data) {
+        return Ok(data.to_vec());
+    }
+    let mode = data[4];
+    let mut length = [0u8; 8];
+    length.copy_from_slice(&data[5..HEADER_LEN]);
+    let length = u64::from_le_bytes(length) as usize;
+
+    let payload = decompress_to_vec_with_limit(&data[HEADER_LEN..], length)
+        .map_err(|e| TtsError::Decompression(format!("{:?}", e.status)))?;
+    if payload.len() != length {
+        return Err(TtsError::Decompression(format!(
+            "expected {} bytes, got {}",
+            length,
+            payload.len()
+        )));
+    }
+    match mode {
+        MODE_RAW => Ok(payload),
+        MODE_PCM16_DELTA => Ok(delta_decode(&payload)),
+        mode => Err(TtsError::Decompression(format!("unknown mode {}", mode))),
+    }
+}
+
+pub fn is_compressed(data: &[u8]) -> bool {
+    data.len() >= HEADER_LEN && data.starts_with(MAGIC)
+}
+
+// audio in a form suitable for sending to another process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompressedAudio {
+    pub format: AudioFormat,
+    pub data: Vec<u8>,
+}
+
+impl CompressedAudio {
+    pub fn new(audio: &[u8], format: AudioFormat) -> Self {
+        Self {
+            data: compress_audio(audio, &format),
+            format,
+        }
+    }
+
+    pub fn decompress(&self) -> Result<Vec<u8>, TtsError> {
+        decompress_audio(&self.data)
+    }
+}
+
+pub fn write_compressed(path: &str, audio: &[u8], format: &AudioFormat) -> Result<(), TtsError> {
+    write_file(path, &compress_audio(audio, format))
+}
+
+// reads audio written compressed or uncompressed
+pub fn read_audio(path: &str) -> Result<Vec<u8>, TtsError> {
+    let file = vfs::open_file(path, false, None).map_err(|e| TtsError::Vfs(e.to_string()))?;
+    let bytes = file.read().map_err(|e| TtsError::Vfs(e.to_string()))?;
+    decompress_audio(&bytes)
+}
+
+fn delta_encode(pcm: &[u8]) -> Vec<u8> {
+    let mut previous = 0i16;
+    pcm.chunks_exact(2)
+        .flat_map(|bytes| {
+            let sample = i16::from_le_bytes([bytes[0], bytes[1]]);
+            let delta = sample.wrapping_sub(previous);
+            previous = sample;
+            delta.to_le_bytes()
+        })
+        .collect()
+}
+
+fn delta_decode(deltas: &[u8]) -> Vec<u8> {
+    let mut previous = 0i16;
+    deltas
+        .chunks_exact(2)
+        .flat_map(|bytes| {
+            previous = previous.wrapping_add(i16::from_le_bytes([bytes[0], bytes[1]]));
+            previous.to_le_bytes()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pcm(samples: &[i16]) -> Vec<u8> {
+        samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+    }
+
+    fn header(mode: u8, length: u64) -> Vec<u8> {
+        let mut header = MAGIC.to_vec();
+        header.push(mode);
+        header.extend_from_slice(&length.to_le_bytes());
+        header
+    }
+
+    #[test]
+    fn header_layout() {
+        let compressed = compress_audio(b"mp3", &AudioFormat::Mp3_44100_128);
+        assert_eq!(&compressed[..HEADER_LEN], header(MODE_RAW, 3).as_slice());
+        let compressed = compress_audio(&pcm(&[1, 2]), &AudioFormat::Pcm16000);
+        assert_eq!(
+            &compressed[..HEADER_LEN],
+            header(MODE_PCM16_DELTA, 4).as_slice()
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        let samples = pcm(&[0, i16::MAX, i16::MIN, -1, 1, 300, -300]);
+        let compressed = compress_audio(&samples, &AudioFormat::Pcm24000);
+        assert_eq!(decompress_audio(&compressed).unwrap(), samples);
+
+        let mp3 = [0xff, 0xfb, 0x90, 0x00, 0x01];
+        let compressed = compress_audio(&mp3, &AudioFormat::Mp3_44100_128);
+        assert_eq!(decompress_audio(&compressed).unwrap(), mp3);
+    }
+
+    #[test]
+    fn odd_length_pcm_is_stored_raw() {
+        let compressed = compress_audio(&[1, 2, 3], &AudioFormat::Pcm16000);
+        assert_eq!(compressed[4], MODE_RAW);
+        assert_eq!(decompress_audio(&compressed).unwrap(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn uncompressed_data_passes_through() {
+        assert_eq!(decompress_audio(b"").unwrap(), b"");
+        assert_eq!(decompress_audio(b"HWAZ").unwrap(), b"HWAZ");
+        assert!(!is_compressed(
+            b"ID3\x04\x00\x00\x00\x00\x00\x00\x00\x00\x00"
+        ));
+    }
+
+    #[test]
+    fn rejects_corrupt_data() {
+        let compressed = compress_audio(&pcm(&[5; 64]), &AudioFormat::Pcm16000);
+        let truncated = &compressed[..compressed.len() - 2];
+        assert!(matches!(
+            decompress_audio(truncated),
+            Err(TtsError::Decompression(_))
+        ));
+
+        let mut wrong_length = header(MODE_RAW, 10);
+        wrong_length.extend_from_slice(&compress_to_vec(b"ab", LEVEL));
+        assert!(matches!(
+            decompress_audio(&wrong_length),
+            Err(TtsError::Decompression(reason)) if reason == "expected 10 bytes, got 2"
+        ));
+
+        let mut unknown_mode = header(9, 2);
+        unknown_mode.extend_from_slice(&compress_to_vec(b"ab", LEVEL));
+        assert!(matches!(
+            decompress_audio(&unknown_mode),
+            Err(TtsError::Decompression(reason)) if reason == "unknown mode 9"
+        ));
+    }
+
+    #[test]
+    fn delta_coding_wraps() {
+        let samples = pcm(&[i16::MIN, i16::MAX, i16::MIN]);
+        assert_eq!(delta_decode(&delta_encode(&samples)), samples);
+    }
+}
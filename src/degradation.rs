@@ -0,0 +1,124 @@
+use crate::audio::mp3_parameters;
+use crate::billing::credit_multiplier;
+use crate::types::{AudioFormat, SpeechRequest, TtsModel};
+
+// the response tag naming what a degraded request gave up, e.g. "model,output_format"
+pub const DEGRADED_TAG: &str = "degraded";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuotaThreshold {
+    // characters left in the billing period
+    Characters(u64),
+    // share of the period's limit left, from 0.0 to 1.0
+    Fraction(f64),
+}
+
+impl QuotaThreshold {
+    fn reached(&self, remaining: u64, limit: u64) -> bool {
+        match *self {
+            QuotaThreshold::Characters(characters) => remaining < characters,
+            QuotaThreshold::Fraction(fraction) => (remaining as f64) < limit as f64 * fraction,
+        }
+    }
+}
+
+// what requests give up once remaining quota falls below the threshold, so the account keeps
+// working to the end of the period instead of failing with quota_exceeded. Each step only
+// ever makes a request cheaper: the model is switched only for one billed at a lower rate,
+// and the format only for a lower mp3 bitrate, so pcm and µ-law requests keep their format.
+#[derive(Debug, Clone)]
+pub struct DegradationPolicy {
+    threshold: QuotaThreshold,
+    model: Option<TtsModel>,
+    output_format: Option<AudioFormat>,
+    drop_context: bool,
+}
+
+impl DegradationPolicy {
+    pub fn new(threshold: QuotaThreshold) -> Self {
+        Self {
+            threshold,
+            model: Some(TtsModel::ElevenFlashV25),
+            output_format: Some(AudioFormat::Mp3_22050_32),
+            drop_context: true,
+        }
+    }
+
+    pub fn below_characters(characters: u64) -> Self {
+        Self::new(QuotaThreshold::Characters(characters))
+    }
+
+    pub fn below_fraction(fraction: f64) -> Self {
+        Self::new(QuotaThreshold::Fraction(fraction.clamp(0.0, 1.0)))
+    }
+
+    pub fn model(mut self, model: TtsModel) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    pub fn keep_model(mut self) -> Self {
+        self.model = None;
+        self
+    }
+
+    pub fn output_format(mut self, format: AudioFormat) -> Self {
+        self.output_format = Some(format);
+        self
+    }
+
+    pub fn keep_output_format(mut self) -> Self {
+        self.output_format = None;
+        self
+    }
+
+    // keep previous_text and next_text
+    pub fn keep_context(mut self) -> Self {
+        self.drop_context = false;
+        self
+    }
+
+    pub fn threshold(&self) -> QuotaThreshold {
+        self.threshold
+    }
+
+    pub fn applies(&self, remaining: u64, limit: u64) -> bool {
+        self.threshold.reached(remaining, limit)
+    }
+
+    // degrades the request and tags it with what changed; a request with nothing to give up
+    // is left untagged
+    pub fn apply(&self, mut request: SpeechRequest) -> SpeechRequest {
+        let mut changes = Vec::new();
+
+        if let Some(ref model) = self.model {
+            if credit_multiplier(model) < credit_multiplier(&request.model) {
+                request.model = model.clone();
+                changes.push("model");
+            }
+        }
+        if let Some(ref format) = self.output_format {
+            let current = request.output_format.clone().unwrap_or_default();
+            let lower_bitrate = match (mp3_parameters(&current), mp3_parameters(format)) {
+                (Some((_, current)), Some((_, target))) => target < current,
+                _ => false,
+            };
+            if lower_bitrate {
+                request.output_format = Some(format.clone());
+                changes.push("output_format");
+            }
+        }
+        if self.drop_context && (request.previous_text.is_some() || request.next_text.is_some()) {
+            request.previous_text = None;
+            request.next_text = None;
+            changes.push("context");
+        }
+
+        if !changes.is_empty() {
+            request
+                .tags
+                .insert(DEGRADED_TAG.to_string(), changes.join(","));
+        }
+        request
+    }
+}
@@ -0,0 +1,80 @@
+use crate::types::{TtsModel, VoiceSettings};
+use std::collections::HashMap;
+
+// (name, stability, style, speed) for the v2 and v2.5 models
+const DEFAULT_DELIVERIES: [(&str, f32, f32, f32); 3] = [
+    ("urgent", 0.3, 0.6, 1.15),
+    ("soothing", 0.75, 0.2, 0.85),
+    ("newsreader", 0.85, 0.0, 1.0),
+];
+// eleven_v3 only accepts stability 0.0 (creative), 0.5 (natural), or 1.0 (robust)
+const V3_STABILITY: [(&str, f32); 3] = [("urgent", 0.0), ("soothing", 0.5), ("newsreader", 1.0)];
+
+// named deliveries mapped to voice settings, with optional per-model overrides for models
+// whose settings behave differently
+#[derive(Debug, Clone)]
+pub struct DeliveryPresets {
+    presets: HashMap<String, VoiceSettings>,
+    model_presets: HashMap<(String, String), VoiceSettings>,
+}
+
+impl Default for DeliveryPresets {
+    fn default() -> Self {
+        let mut presets = Self::empty();
+        for (name, stability, style, speed) in DEFAULT_DELIVERIES {
+            let settings = VoiceSettings {
+                stability: Some(stability),
+                style: Some(style),
+                speed: Some(speed),
+                ..VoiceSettings::default()
+            };
+            presets = presets.set(name, settings);
+        }
+        for (name, stability) in V3_STABILITY {
+            let settings = VoiceSettings {
+                stability: Some(stability),
+                ..presets.presets[name].clone()
+            };
+            presets = presets.set_for_model(name, TtsModel::ElevenV3, settings);
+        }
+        presets
+    }
+}
+
+impl DeliveryPresets {
+    pub fn empty() -> Self {
+        Self {
+            presets: HashMap::new(),
+            model_presets: HashMap::new(),
+        }
+    }
+
+    pub fn set(mut self, name: impl Into<String>, settings: VoiceSettings) -> Self {
+        self.presets.insert(name.into(), settings);
+        self
+    }
+
+    pub fn set_for_model(
+        mut self,
+        name: impl Into<String>,
+        model: TtsModel,
+        settings: VoiceSettings,
+    ) -> Self {
+        self.model_presets
+            .insert((name.into(), model.as_str().to_string()), settings);
+        self
+    }
+
+    pub fn get(&self, name: &str, model: &TtsModel) -> Option<VoiceSettings> {
+        self.model_presets
+            .get(&(name.to_string(), model.as_str().to_string()))
+            .or_else(|| self.presets.get(name))
+            .cloned()
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.presets.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+}
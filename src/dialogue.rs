@@ -0,0 +1,156 @@
+use crate::audio::silence;
+use crate::client::SpeechClient;
+use crate::error::TtsError;
+use crate::types::{AudioFormat, SpeechRequest, TtsModel, Voice, VoiceSettings};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const DEFAULT_GAP_MS: u64 = 400;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DialogueLine {
+    pub speaker: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DialogueScript {
+    speakers: BTreeMap<String, (Voice, Option<VoiceSettings>)>,
+    lines: Vec<DialogueLine>,
+    gap_ms: u64,
+    template: SpeechRequest,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DialogueLineTiming {
+    pub index: usize,
+    pub speaker: String,
+    pub text: String,
+    pub start_ms: u64,
+    pub duration_ms: u64,
+    pub request_id: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DialogueAudio {
+    pub audio_data: Vec<u8>,
+    pub format: AudioFormat,
+    pub duration_ms: u64,
+    pub lines: Vec<DialogueLineTiming>,
+}
+
+impl Default for DialogueScript {
+    fn default() -> Self {
+        Self {
+            speakers: BTreeMap::new(),
+            lines: Vec::new(),
+            gap_ms: DEFAULT_GAP_MS,
+            template: SpeechRequest::default(),
+        }
+    }
+}
+
+impl DialogueScript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn speaker(mut self, name: impl Into<String>, voice: Voice) -> Self {
+        self.speakers.insert(name.into(), (voice, None));
+        self
+    }
+
+    pub fn speaker_with_settings(
+        mut self,
+        name: impl Into<String>,
+        voice: Voice,
+        settings: VoiceSettings,
+    ) -> Self {
+        self.speakers.insert(name.into(), (voice, Some(settings)));
+        self
+    }
+
+    pub fn line(mut self, speaker: impl Into<String>, text: impl Into<String>) -> Self {
+        self.lines.push(DialogueLine {
+            speaker: speaker.into(),
+            text: text.into(),
+        });
+        self
+    }
+
+    pub fn gap_ms(mut self, gap_ms: u64) -> Self {
+        self.gap_ms = gap_ms;
+        self
+    }
+
+    pub fn model(mut self, model: TtsModel) -> Self {
+        self.template.model = model;
+        self
+    }
+
+    pub fn output_format(mut self, format: AudioFormat) -> Self {
+        self.template.output_format = Some(format);
+        self
+    }
+
+    pub fn language_code(mut self, code: impl Into<String>) -> Self {
+        self.template.language_code = Some(code.into());
+        self
+    }
+
+    pub fn lines(&self) -> &[DialogueLine] {
+        &self.lines
+    }
+
+    pub async fn synthesize(&self, client: &SpeechClient) -> Result<DialogueAudio, TtsError> {
+        // fail before spending credits on the lines that precede an unknown speaker
+        if let Some(line) = self
+            .lines
+            .iter()
+            .find(|line| !self.speakers.contains_key(&line.speaker))
+        {
+            return Err(TtsError::UnknownSpeaker(line.speaker.clone()));
+        }
+
+        let format = self.template.output_format.clone().unwrap_or_default();
+        let gap = silence(&format, self.gap_ms);
+        let gap_ms = format.estimated_duration_ms(gap.len());
+
+        let mut audio = Vec::new();
+        let mut timings = Vec::new();
+        let mut position_ms = 0;
+
+        for (index, line) in self.lines.iter().enumerate() {
+            let (voice, settings) = &self.speakers[&line.speaker];
+            let mut request = self.template.clone();
+            request.text = line.text.clone();
+            request.voice = voice.clone();
+            request.voice_settings = settings.clone();
+
+            let response = client.send_speech_request(request).await?;
+            if index > 0 {
+                audio.extend_from_slice(&gap);
+                position_ms += gap_ms;
+            }
+
+            let duration_ms = format.estimated_duration_ms(response.audio_data.len());
+            audio.extend_from_slice(&response.audio_data);
+            timings.push(DialogueLineTiming {
+                index,
+                speaker: line.speaker.clone(),
+                text: line.text.clone(),
+                start_ms: position_ms,
+                duration_ms,
+                request_id: response.request_id,
+            });
+            position_ms += duration_ms;
+        }
+
+        Ok(DialogueAudio {
+            audio_data: audio,
+            format,
+            duration_ms: position_ms,
+            lines: timings,
+        })
+    }
+}
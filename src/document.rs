@@ -0,0 +1,324 @@
+use crate::client::SpeechClient;
+use crate::error::TtsError;
+use crate::hash::sha256_hex;
+use crate::request_presets::RequestPreset;
+use crate::text::{chunk_text, split_sentences};
+use crate::types::{AudioFormat, SpeechRequest, TtsModel, Voice, VoiceSettings};
+use crate::util::{read_file, write_file};
+use hyperware_process_lib::vfs;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const SEGMENT_DIRECTORY: &str = "segments";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Segmentation {
+    #[default]
+    Sentence,
+    // paragraphs are separated by blank lines
+    Paragraph,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentManifest {
+    pub format: AudioFormat,
+    pub segmentation: Segmentation,
+    // voice, model, and settings every segment was rendered with
+    pub request: RequestPreset,
+    pub path: String,
+    pub sha256: String,
+    pub duration_ms: u64,
+    pub segments: Vec<SegmentManifest>,
+    // segments synthesized by the last render, and segments reused from the cache
+    pub synthesized: usize,
+    pub reused: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentManifest {
+    pub index: usize,
+    pub text: String,
+    // covers the text and the render settings, so changing either re-synthesizes
+    pub key: String,
+    pub path: String,
+    pub duration_ms: u64,
+}
+
+// renders a document into `{directory}/document.<ext>`, caching audio per segment in
+// `{directory}/segments/`; re-rendering edited text only synthesizes segments whose text
+// changed and reassembles the rest from the cache
+pub struct DocumentBuilder<'a> {
+    client: &'a SpeechClient,
+    directory: String,
+    text: String,
+    segmentation: Segmentation,
+    template: SpeechRequest,
+}
+
+impl<'a> DocumentBuilder<'a> {
+    pub(crate) fn new(client: &'a SpeechClient, directory: String) -> Self {
+        Self {
+            client,
+            directory: directory.trim_end_matches('/').to_string(),
+            text: String::new(),
+            segmentation: Segmentation::default(),
+            template: SpeechRequest::default(),
+        }
+    }
+
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = text.into();
+        self
+    }
+
+    pub fn segmentation(mut self, segmentation: Segmentation) -> Self {
+        self.segmentation = segmentation;
+        self
+    }
+
+    pub fn model(mut self, model: TtsModel) -> Self {
+        self.template.model = model;
+        self
+    }
+
+    pub fn voice(mut self, voice: Voice) -> Self {
+        self.template.voice = voice;
+        self
+    }
+
+    pub fn voice_settings(mut self, settings: VoiceSettings) -> Self {
+        self.template.voice_settings = Some(settings);
+        self
+    }
+
+    pub fn output_format(mut self, format: AudioFormat) -> Self {
+        self.template.output_format = Some(format);
+        self
+    }
+
+    pub fn language_code(mut self, code: impl Into<String>) -> Self {
+        self.template.language_code = Some(code.into());
+        self
+    }
+
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.template.seed = Some(seed);
+        self
+    }
+
+    pub async fn execute(self) -> Result<DocumentManifest, TtsError> {
+        let max_length = self.client.max_input_length(&self.template.model);
+        let texts = segment(&self.text, self.segmentation, max_length);
+        if texts.is_empty() {
+            return Err(TtsError::MissingInput);
+        }
+        let format = self.template.output_format.clone().unwrap_or_default();
+        let request = RequestPreset::from_request(&self.template);
+        let settings_sha256 = settings_hash(&request)?;
+        let segment_directory = format!("{}/{}", self.directory, SEGMENT_DIRECTORY);
+        vfs::open_dir(&self.directory, true, None).map_err(|e| TtsError::Vfs(e.to_string()))?;
+        vfs::open_dir(&segment_directory, true, None).map_err(|e| TtsError::Vfs(e.to_string()))?;
+
+        let renderer = SegmentRenderer {
+            client: self.client,
+            template: &self.template,
+            settings_sha256,
+            segment_directory,
+            format: &format,
+        };
+        let mut segments = Vec::with_capacity(texts.len());
+        let mut synthesized = 0;
+        for index in 0..texts.len() {
+            let (segment, fresh) = renderer.render(&texts, index).await?;
+            segments.push(segment);
+            synthesized += fresh as usize;
+        }
+
+        let mut manifest = DocumentManifest {
+            format: format.clone(),
+            segmentation: self.segmentation,
+            request,
+            path: format!("{}/document.{}", self.directory, format.extension()),
+            sha256: String::new(),
+            duration_ms: 0,
+            reused: segments.len() - synthesized,
+            segments,
+            synthesized,
+        };
+        assemble(&self.directory, &mut manifest)?;
+        Ok(manifest)
+    }
+}
+
+impl DocumentManifest {
+    pub fn load(directory: &str) -> Result<Self, TtsError> {
+        let directory = directory.trim_end_matches('/');
+        let path = format!("{}/{}", directory, MANIFEST_FILE);
+        let bytes = read_file(&path)?
+            .ok_or_else(|| TtsError::Vfs(format!("no document manifest at {}", path)))?;
+        serde_json::from_slice(&bytes).map_err(|e| TtsError::DeserializationError(e.to_string()))
+    }
+
+    pub fn segment_audio(&self, index: usize) -> Result<Vec<u8>, TtsError> {
+        let segment = self.segment(index)?;
+        read_file(&segment.path)?
+            .ok_or_else(|| TtsError::Vfs(format!("missing cached segment {}", segment.path)))
+    }
+
+    fn segment(&self, index: usize) -> Result<&SegmentManifest, TtsError> {
+        self.segments.get(index).ok_or(TtsError::SegmentOutOfRange {
+            index,
+            segments: self.segments.len(),
+        })
+    }
+}
+
+// replaces one segment's text in a rendered document, synthesizes just that segment with its
+// neighbours as context, and splices it into the document audio
+pub(crate) async fn patch_segment(
+    client: &SpeechClient,
+    directory: &str,
+    index: usize,
+    text: String,
+) -> Result<DocumentManifest, TtsError> {
+    let directory = directory.trim_end_matches('/');
+    let mut manifest = DocumentManifest::load(directory)?;
+    manifest.segment(index)?;
+
+    let mut template = manifest.request.apply(SpeechRequest::default());
+    template.output_format = Some(manifest.format.clone());
+    let mut texts: Vec<String> = manifest.segments.iter().map(|s| s.text.clone()).collect();
+    texts[index] = text;
+
+    let format = manifest.format.clone();
+    let renderer = SegmentRenderer {
+        client,
+        template: &template,
+        settings_sha256: settings_hash(&manifest.request)?,
+        segment_directory: format!("{}/{}", directory, SEGMENT_DIRECTORY),
+        format: &format,
+    };
+    let (segment, fresh) = renderer.render(&texts, index).await?;
+    manifest.segments[index] = segment;
+    manifest.synthesized = fresh as usize;
+    manifest.reused = manifest.segments.len() - manifest.synthesized;
+    assemble(directory, &mut manifest)?;
+    Ok(manifest)
+}
+
+struct SegmentRenderer<'a> {
+    client: &'a SpeechClient,
+    template: &'a SpeechRequest,
+    settings_sha256: String,
+    segment_directory: String,
+    format: &'a AudioFormat,
+}
+
+impl SegmentRenderer<'_> {
+    // reuses cached audio for the segment's text when there is any; the flag reports
+    // whether the segment was synthesized
+    async fn render(
+        &self,
+        texts: &[String],
+        index: usize,
+    ) -> Result<(SegmentManifest, bool), TtsError> {
+        let text = &texts[index];
+        let key = sha256_hex(format!("{}\n{}", self.settings_sha256, text).as_bytes());
+        let path = format!(
+            "{}/{}.{}",
+            self.segment_directory,
+            key,
+            self.format.extension()
+        );
+        let (audio, fresh) = match read_file(&path)? {
+            Some(audio) => (audio, false),
+            None => {
+                let audio = self.synthesize(texts, index).await?;
+                write_file(&path, &audio)?;
+                (audio, true)
+            }
+        };
+        let segment = SegmentManifest {
+            index,
+            text: text.clone(),
+            key,
+            path,
+            duration_ms: self.format.estimated_duration_ms(audio.len()),
+        };
+        Ok((segment, fresh))
+    }
+
+    // with the neighbouring segments as context, so prosody carries across the joins
+    async fn synthesize(&self, texts: &[String], index: usize) -> Result<Vec<u8>, TtsError> {
+        let mut request = self.template.clone();
+        request.text = texts[index].clone();
+        request.previous_text = index.checked_sub(1).map(|i| texts[i].clone());
+        request.next_text = texts.get(index + 1).cloned();
+        Ok(self.client.send_speech_request(request).await?.audio_data)
+    }
+}
+
+// joins the cached segments into the document audio, writes the manifest, and removes
+// segments the document no longer uses
+fn assemble(directory: &str, manifest: &mut DocumentManifest) -> Result<(), TtsError> {
+    let mut audio = Vec::new();
+    for segment in &manifest.segments {
+        let bytes = read_file(&segment.path)?
+            .ok_or_else(|| TtsError::Vfs(format!("missing cached segment {}", segment.path)))?;
+        audio.extend(bytes);
+    }
+    write_file(&manifest.path, &audio)?;
+    manifest.sha256 = sha256_hex(&audio);
+    manifest.duration_ms = manifest.format.estimated_duration_ms(audio.len());
+
+    let manifest_json = serde_json::to_vec_pretty(manifest)
+        .map_err(|e| TtsError::SerializationError(e.to_string()))?;
+    write_file(&format!("{}/{}", directory, MANIFEST_FILE), &manifest_json)?;
+    prune_segments(directory, manifest)
+}
+
+pub(crate) fn settings_hash(request: &RequestPreset) -> Result<String, TtsError> {
+    let json =
+        serde_json::to_vec(request).map_err(|e| TtsError::SerializationError(e.to_string()))?;
+    Ok(sha256_hex(&json))
+}
+
+// each segment fits in a single request; longer sentences or paragraphs are split further
+fn segment(text: &str, segmentation: Segmentation, max_length: usize) -> Vec<String> {
+    let units: Vec<&str> = match segmentation {
+        Segmentation::Sentence => split_sentences(text),
+        Segmentation::Paragraph => text
+            .split("\n\n")
+            .map(str::trim)
+            .filter(|paragraph| !paragraph.is_empty())
+            .collect(),
+    };
+    units
+        .into_iter()
+        .flat_map(|unit| chunk_text(unit, max_length))
+        .collect()
+}
+
+fn prune_segments(directory: &str, manifest: &DocumentManifest) -> Result<(), TtsError> {
+    let segment_directory = format!("{}/{}", directory, SEGMENT_DIRECTORY);
+    let used: HashSet<&str> = manifest
+        .segments
+        .iter()
+        .filter_map(|segment| segment.path.rsplit('/').next())
+        .collect();
+    let dir =
+        vfs::open_dir(&segment_directory, false, None).map_err(|e| TtsError::Vfs(e.to_string()))?;
+    let entries = dir.read().map_err(|e| TtsError::Vfs(e.to_string()))?;
+    for entry in entries {
+        let Some(file_name) = entry.path.rsplit('/').next() else {
+            continue;
+        };
+        if !used.contains(file_name) {
+            vfs::remove_file(&format!("{}/{}", segment_directory, file_name), None)
+                .map_err(|e| TtsError::Vfs(e.to_string()))?;
+        }
+    }
+    Ok(())
+}
@@ -6,17 +6,41 @@ pub enum TtsError {
     #[error("missing input text")]
     MissingInput,
 
-    #[error("input text too long: {0} characters (max: 5000)")]
-    InputTooLong(usize),
+    #[error("input text too long: {length} characters (max: {max})")]
+    InputTooLong { length: usize, max: usize },
 
     #[error("invalid voice setting {field}: {value} (must be between 0.0 and 1.0)")]
     InvalidVoiceSettings { field: String, value: f32 },
 
+    #[error("invalid speed: {0} (must be between 0.7 and 1.2)")]
+    InvalidSpeed(f32),
+
+    #[error("invalid voice ID {0:?} (expected 20 alphanumeric characters)")]
+    InvalidVoiceId(String),
+
+    #[error("invalid pronunciation rule: {0}")]
+    InvalidPronunciationRule(String),
+
+    #[error("invalid redaction pattern: {0}")]
+    InvalidRedactionPattern(String),
+
     #[error("missing API key")]
     MissingApiKey,
 
+    #[error("invalid API key")]
+    InvalidApiKey,
+
+    #[error("forbidden: {reason}")]
+    Forbidden { reason: String },
+
     #[error("API error (status {status}): {message}")]
-    ApiError { status: u16, message: String },
+    ApiError {
+        status: u16,
+        code: Option<String>,
+        message: String,
+        // from the Retry-After header, when it gives a number of seconds
+        retry_after_ms: Option<u64>,
+    },
 
     #[error("HTTP client error: {0}")]
     HttpClient(#[from] HttpClientError),
@@ -27,6 +51,138 @@ pub enum TtsError {
     #[error("deserialization error: {0}")]
     DeserializationError(String),
 
+    #[error("ID3 tags require an MP3 output format, got {0}")]
+    Id3UnsupportedFormat(String),
+
+    #[error("WAV export requires a PCM output format, got {0}")]
+    WavUnsupportedFormat(String),
+
+    #[error("response too large: {size} bytes (max: {limit})")]
+    ResponseTooLarge { size: usize, limit: usize },
+
+    #[error("response is not {expected} audio (content-type: {content_type:?})")]
+    UnexpectedContent {
+        expected: String,
+        content_type: Option<String>,
+    },
+
+    #[error("empty or truncated audio: {size} bytes for {text_length} characters of input")]
+    EmptyAudio { size: usize, text_length: usize },
+
+    #[error("no connectivity; request queued for later delivery as {0}")]
+    Queued(String),
+
+    #[error("decompression failed: {0}")]
+    Decompression(String),
+
+    #[error("VFS error: {0}")]
+    Vfs(String),
+
+    #[error("model {0} is not available to this account")]
+    ModelNotAvailable(String),
+
+    #[error("output format {format} requires the {required_tier} tier or above")]
+    FormatNotAllowed {
+        format: String,
+        required_tier: String,
+    },
+
+    #[error("input text too long for this plan: {length} characters (max: {limit})")]
+    ExceedsPlanLimit { length: usize, limit: usize },
+
+    #[error("no voice settings preset {name:?} for voice {voice}")]
+    PresetNotFound { voice: String, name: String },
+
+    #[error("input contains blocked term {0:?}")]
+    BlockedTerm(String),
+
+    #[error("invalid profanity list: {0}")]
+    InvalidProfanityList(String),
+
+    #[error("unknown request preset {0:?}")]
+    UnknownRequestPreset(String),
+
+    #[error("invalid request preset name {0:?}")]
+    InvalidPresetName(String),
+
+    #[error("unknown delivery preset {0:?}")]
+    UnknownDelivery(String),
+
+    #[error("no glossary {name:?} version {version}")]
+    GlossaryNotFound { name: String, version: u32 },
+
+    #[error("unknown voice alias {0:?}")]
+    UnknownVoiceAlias(String),
+
+    #[error("dialogue line for unknown speaker {0:?}")]
+    UnknownSpeaker(String),
+
+    #[error("segment {index} out of range (document has {segments} segments)")]
+    SegmentOutOfRange { index: usize, segments: usize },
+
+    #[error("no pooled client for {0}")]
+    NoPooledClient(String),
+
+    #[error("KV error: {0}")]
+    Kv(String),
+
+    #[error("invalid webhook signature: {0}")]
+    InvalidWebhookSignature(String),
+
+    #[error("invalid signed url: {0}")]
+    InvalidSignedUrl(String),
+
     #[error("invalid seed value: {0} (must be between 0 and 4294967295)")]
     InvalidSeed(u32),
 }
+
+const MODEL_UNAVAILABLE_CODES: [&str; 4] = [
+    "model_access_denied",
+    "model_not_found",
+    "system_busy",
+    "too_many_concurrent_requests",
+];
+
+impl TtsError {
+    pub fn is_model_unavailable(&self) -> bool {
+        match self {
+            TtsError::ApiError { status, code, .. } => {
+                *status == 503
+                    || code
+                        .as_deref()
+                        .is_some_and(|code| MODEL_UNAVAILABLE_CODES.contains(&code))
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_voice_not_found(&self) -> bool {
+        matches!(
+            self,
+            TtsError::ApiError { code: Some(code), .. } if code == "voice_not_found"
+        )
+    }
+    pub fn retry_after_ms(&self) -> Option<u64> {
+        match self {
+            TtsError::ApiError { retry_after_ms, .. } => *retry_after_ms,
+            _ => None,
+        }
+    }
+
+    // network failures, rate limiting, and server-side errors; never client errors like 401 or 422
+    pub fn is_transient(&self) -> bool {
+        match self {
+            TtsError::HttpClient(_) => true,
+            TtsError::ApiError { status, .. } => *status == 429 || (500..600).contains(status),
+            _ => false,
+        }
+    }
+
+    // a 4xx other than 429: the API refused the request itself, so resending it won't help
+    pub fn is_client_error(&self) -> bool {
+        matches!(
+            self,
+            TtsError::ApiError { status, .. } if (400..500).contains(status) && *status != 429
+        )
+    }
+}
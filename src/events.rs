@@ -0,0 +1,56 @@
+use serde::Serialize;
+
+// every event carries the request's correlation id: the one set on the request, or one the
+// client assigns ("req-1", "req-2", ...) when events are enabled
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum RequestEvent {
+    Queued {
+        correlation_id: String,
+        text_length: usize,
+    },
+    // one per attempt sent to the API, including retries and fallbacks
+    Started {
+        correlation_id: String,
+        attempt: u32,
+        model: String,
+        voice_id: String,
+    },
+    // one per input chunk when the input length policy splits a request
+    ChunkReceived {
+        correlation_id: String,
+        chunk: usize,
+        chunks: usize,
+        bytes: usize,
+    },
+    Retried {
+        correlation_id: String,
+        attempt: u32,
+        delay_ms: u64,
+        error: String,
+    },
+    Completed {
+        correlation_id: String,
+        bytes: usize,
+        request_id: Option<String>,
+        elapsed_ms: u64,
+    },
+    Failed {
+        correlation_id: String,
+        error: String,
+        elapsed_ms: u64,
+    },
+}
+
+impl RequestEvent {
+    pub fn correlation_id(&self) -> &str {
+        match self {
+            RequestEvent::Queued { correlation_id, .. }
+            | RequestEvent::Started { correlation_id, .. }
+            | RequestEvent::ChunkReceived { correlation_id, .. }
+            | RequestEvent::Retried { correlation_id, .. }
+            | RequestEvent::Completed { correlation_id, .. }
+            | RequestEvent::Failed { correlation_id, .. } => correlation_id,
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::error::TtsError;
+use crate::types::{SpeechRequestJson, SpeechResponse};
+use crate::util::write_file;
+use crate::wav::{response_to_wav, CueGranularity};
+use hyperware_process_lib::vfs;
+use serde_json::json;
+
+const DEFAULT_LAYOUT: &str = "{voice}/{sha256}";
+
+pub struct CorpusExporter {
+    root: String,
+    layout: String,
+    wav: Option<Option<CueGranularity>>,
+}
+
+impl CorpusExporter {
+    pub fn new(root: impl Into<String>) -> Self {
+        Self {
+            root: root.into().trim_end_matches('/').to_string(),
+            layout: DEFAULT_LAYOUT.to_string(),
+            wav: None,
+        }
+    }
+
+    // placeholders: {voice}, {voice_id}, {model}, {language}, {request_id}, {sha256}
+    pub fn layout(mut self, layout: impl Into<String>) -> Self {
+        self.layout = layout.into();
+        self
+    }
+
+    // writes PCM responses as audio.wav instead of raw audio.pcm, with cue markers at
+    // word or sentence boundaries when `cues` is set and the response has alignment
+    pub fn wav(mut self, cues: Option<CueGranularity>) -> Self {
+        self.wav = Some(cues);
+        self
+    }
+
+    // writes audio.<ext>, request.json, and (for requests made with timestamps)
+    // alignment.json; returns the directory written to
+    pub fn export(
+        &self,
+        request: &SpeechRequestJson,
+        response: &SpeechResponse,
+    ) -> Result<String, TtsError> {
+        let relative = self
+            .layout
+            .replace("{voice}", response.voice.name())
+            .replace("{voice_id}", response.voice.as_voice_id())
+            .replace("{model}", response.model.as_str())
+            .replace(
+                "{language}",
+                request.language_code.as_deref().unwrap_or("und"),
+            )
+            .replace(
+                "{request_id}",
+                response
+                    .request_id
+                    .as_deref()
+                    .unwrap_or(&response.audio_sha256),
+            )
+            .replace("{sha256}", &response.audio_sha256);
+
+        let mut directory = self.root.clone();
+        create_dir(&directory)?;
+        for segment in relative.split('/').filter(|s| !s.is_empty()) {
+            directory = format!("{}/{}", directory, segment);
+            create_dir(&directory)?;
+        }
+
+        match self.wav {
+            Some(cues) if response.format.extension() == "pcm" => write_file(
+                &format!("{}/audio.wav", directory),
+                &response_to_wav(response, cues)?,
+            )?,
+            _ => write_file(
+                &format!("{}/audio.{}", directory, response.format.extension()),
+                &response.audio_data,
+            )?,
+        }
+
+        let request_json = json!({
+            "voice_id": response.voice.as_voice_id(),
+            "voice": response.voice.name(),
+            "model": response.model.as_str(),
+            "output_format": response.format.as_str(),
+            "request_id": response.request_id,
+            "audio_sha256": response.audio_sha256,
+            "tags": response.tags,
+            "request": request,
+        });
+        write_json(&format!("{}/request.json", directory), &request_json)?;
+
+        if response.alignment.is_some() || response.normalized_alignment.is_some() {
+            let alignment_json = json!({
+                "alignment": response.alignment,
+                "normalized_alignment": response.normalized_alignment,
+            });
+            write_json(&format!("{}/alignment.json", directory), &alignment_json)?;
+        }
+
+        Ok(directory)
+    }
+}
+
+fn create_dir(path: &str) -> Result<(), TtsError> {
+    vfs::open_dir(path, true, None)
+        .map(|_| ())
+        .map_err(|e| TtsError::Vfs(e.to_string()))
+}
+
+fn write_json(path: &str, value: &serde_json::Value) -> Result<(), TtsError> {
+    let bytes = serde_json::to_vec_pretty(value)
+        .map_err(|e| TtsError::SerializationError(e.to_string()))?;
+    write_file(path, &bytes)
+}
@@ -0,0 +1,115 @@
+use crate::util::now_ms;
+use std::sync::{Mutex, MutexGuard};
+
+const DEFAULT_THRESHOLD: u32 = 3;
+const DEFAULT_RETRY_PRIMARY_MS: u64 = 5 * 60 * 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailoverStats {
+    pub active_base_url: String,
+    pub on_secondary: bool,
+    pub consecutive_failures: u32,
+    pub failovers: u64,
+    pub failbacks: u64,
+}
+
+#[derive(Debug, Default)]
+struct FailoverState {
+    on_secondary: bool,
+    consecutive_failures: u32,
+    // when the secondary was last chosen, in ms since the epoch
+    switched_at_ms: u64,
+    failovers: u64,
+    failbacks: u64,
+}
+
+// switches to a secondary base URL after `threshold` consecutive connectivity errors on the
+// primary, and probes the primary again once `retry_primary_after_ms` has passed
+#[derive(Debug)]
+pub struct RegionFailover {
+    secondary: String,
+    threshold: u32,
+    retry_primary_after_ms: u64,
+    state: Mutex<FailoverState>,
+}
+
+impl RegionFailover {
+    pub fn new(secondary_base_url: impl Into<String>) -> Self {
+        Self {
+            secondary: secondary_base_url.into(),
+            threshold: DEFAULT_THRESHOLD,
+            retry_primary_after_ms: DEFAULT_RETRY_PRIMARY_MS,
+            state: Mutex::new(FailoverState::default()),
+        }
+    }
+
+    pub fn threshold(mut self, consecutive_errors: u32) -> Self {
+        self.threshold = consecutive_errors.max(1);
+        self
+    }
+
+    pub fn retry_primary_after_ms(mut self, ms: u64) -> Self {
+        self.retry_primary_after_ms = ms;
+        self
+    }
+
+    pub(crate) fn stats(&self, primary: &str) -> FailoverStats {
+        let state = self.lock();
+        FailoverStats {
+            active_base_url: self.pick(&state, primary).to_string(),
+            on_secondary: state.on_secondary,
+            consecutive_failures: state.consecutive_failures,
+            failovers: state.failovers,
+            failbacks: state.failbacks,
+        }
+    }
+
+    // the base URL the next request should use
+    pub(crate) fn base_url<'a>(&'a self, primary: &'a str) -> &'a str {
+        self.pick(&self.lock(), primary)
+    }
+
+    pub(crate) fn record_success(&self, base_url: &str, primary: &str) {
+        let mut state = self.lock();
+        state.consecutive_failures = 0;
+        if state.on_secondary && base_url == primary {
+            state.on_secondary = false;
+            state.failbacks += 1;
+        }
+    }
+
+    pub(crate) fn record_connectivity_error(&self, base_url: &str, primary: &str) {
+        let mut state = self.lock();
+        if base_url != primary {
+            return;
+        }
+        if state.on_secondary {
+            // a failed probe of the primary; stay on the secondary for another interval
+            state.switched_at_ms = now_ms();
+            return;
+        }
+        state.consecutive_failures += 1;
+        if state.consecutive_failures >= self.threshold {
+            state.on_secondary = true;
+            state.switched_at_ms = now_ms();
+            state.consecutive_failures = 0;
+            state.failovers += 1;
+        }
+    }
+
+    fn pick<'a>(&'a self, state: &FailoverState, primary: &'a str) -> &'a str {
+        let probe_primary =
+            now_ms().saturating_sub(state.switched_at_ms) >= self.retry_primary_after_ms;
+        if state.on_secondary && !probe_primary {
+            &self.secondary
+        } else {
+            primary
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, FailoverState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
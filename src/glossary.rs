@@ -0,0 +1,88 @@
+use crate::error::TtsError;
+use crate::util::kv_get;
+use hyperware_process_lib::kv::{self, Kv};
+use hyperware_process_lib::our;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// approved spoken forms for source terms, e.g. "K8s" -> "Kubernetes"
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Glossary {
+    pub name: String,
+    pub version: u32,
+    pub terms: BTreeMap<String, String>,
+}
+
+impl Glossary {
+    // an unsaved glossary; `GlossaryStore::publish` assigns its version
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: 0,
+            terms: BTreeMap::new(),
+        }
+    }
+
+    pub fn term(mut self, source: impl Into<String>, spoken: impl Into<String>) -> Self {
+        self.terms.insert(source.into(), spoken.into());
+        self
+    }
+}
+
+// every published version is kept under "{name}@{version}"; "{name}" holds the latest
+pub struct GlossaryStore {
+    kv: Kv<String, Glossary>,
+}
+
+impl GlossaryStore {
+    pub fn open(db: &str) -> Result<Self, TtsError> {
+        let kv = kv::open(our().package_id(), db, None).map_err(|e| TtsError::Kv(e.to_string()))?;
+        Ok(Self { kv })
+    }
+
+    // stores the glossary as a new version and makes it the latest; versions left
+    // behind by a roll back are never overwritten
+    pub fn publish(&self, glossary: &Glossary) -> Result<Glossary, TtsError> {
+        let mut version = self.latest(&glossary.name).map_or(0, |g| g.version) + 1;
+        while self.version(&glossary.name, version).is_some() {
+            version += 1;
+        }
+        let glossary = Glossary {
+            version,
+            ..glossary.clone()
+        };
+        self.kv
+            .set(&version_key(&glossary.name, version), &glossary, None)
+            .map_err(|e| TtsError::Kv(e.to_string()))?;
+        self.kv
+            .set(&glossary.name, &glossary, None)
+            .map_err(|e| TtsError::Kv(e.to_string()))?;
+        Ok(glossary)
+    }
+
+    pub fn latest(&self, name: &str) -> Option<Glossary> {
+        kv_get(&self.kv, &name.to_string())
+    }
+
+    pub fn version(&self, name: &str, version: u32) -> Option<Glossary> {
+        kv_get(&self.kv, &version_key(name, version))
+    }
+
+    // points the latest entry back at an earlier version without deleting newer ones
+    pub fn roll_back(&self, name: &str, version: u32) -> Result<Glossary, TtsError> {
+        let glossary = self
+            .version(name, version)
+            .ok_or_else(|| TtsError::GlossaryNotFound {
+                name: name.to_string(),
+                version,
+            })?;
+        self.kv
+            .set(&name.to_string(), &glossary, None)
+            .map_err(|e| TtsError::Kv(e.to_string()))?;
+        Ok(glossary)
+    }
+}
+
+fn version_key(name: &str, version: u32) -> String {
+    format!("{}@{}", name, version)
+}
@@ -0,0 +1,306 @@
+use crate::verbalize::ordinal_words;
+
+// containers whose contents are never read aloud
+const SKIPPED_TAGS: [&str; 13] = [
+    "script", "style", "noscript", "head", "nav", "aside", "footer", "pre", "svg", "template",
+    "iframe", "form", "button",
+];
+
+const BLOCK_TAGS: [&str; 21] = [
+    "p",
+    "div",
+    "section",
+    "article",
+    "main",
+    "header",
+    "blockquote",
+    "figcaption",
+    "table",
+    "tr",
+    "dl",
+    "dt",
+    "dd",
+    "br",
+    "hr",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+];
+
+#[derive(Default)]
+struct SpeechTextWriter {
+    blocks: Vec<String>,
+    current: String,
+    pending_space: bool,
+}
+
+impl SpeechTextWriter {
+    fn text(&mut self, text: &str) {
+        let leading_space = text.starts_with(char::is_whitespace);
+        for (i, word) in text.split_whitespace().enumerate() {
+            let attached = i == 0 && !leading_space && !self.pending_space;
+            let punctuation = word.starts_with([',', '.', ';', ':', '!', '?']);
+            if !self.current.is_empty() && !attached && !punctuation {
+                self.current.push(' ');
+            }
+            self.current.push_str(word);
+            self.pending_space = false;
+        }
+        if text.ends_with(char::is_whitespace) {
+            self.pending_space = true;
+        }
+    }
+
+    fn end_block(&mut self) {
+        let mut block = self.current.trim().trim_end_matches(',').to_string();
+        self.current.clear();
+        self.pending_space = false;
+        if block.is_empty() {
+            return;
+        }
+        if !block.ends_with(['.', '!', '?', ':', ';', '…']) {
+            block.push('.');
+        }
+        self.blocks.push(block);
+    }
+
+    fn finish(mut self) -> String {
+        self.end_block();
+        self.blocks.join("\n\n")
+    }
+}
+
+pub fn html_to_speech_text(html: &str) -> String {
+    let mut writer = SpeechTextWriter::default();
+    let mut skipping: Vec<String> = Vec::new();
+    // None for unordered lists, Some(items so far) for ordered lists
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let Some(open) = tag_start(rest) else {
+            if skipping.is_empty() {
+                writer.text(&decode_entities(rest));
+            }
+            break;
+        };
+
+        if open > 0 && skipping.is_empty() {
+            writer.text(&decode_entities(&rest[..open]));
+        }
+        rest = &rest[open..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+
+        // an unclosed tag runs to the end of the input
+        let (tag, consumed) = match tag_end(rest) {
+            Some(end) => (&rest[1..end - 1], end),
+            None => (&rest[1..], rest.len()),
+        };
+        rest = &rest[consumed..];
+
+        let closing = tag.starts_with('/');
+        let self_closing = tag.ends_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if name.is_empty() {
+            continue;
+        }
+
+        if SKIPPED_TAGS.contains(&name.as_str()) {
+            if closing {
+                if let Some(position) = skipping.iter().rposition(|n| *n == name) {
+                    skipping.truncate(position);
+                }
+            } else if !self_closing {
+                skipping.push(name);
+            }
+            continue;
+        }
+        if !skipping.is_empty() {
+            continue;
+        }
+
+        match name.as_str() {
+            "ul" | "ol" => {
+                writer.end_block();
+                if closing {
+                    lists.pop();
+                } else if !self_closing {
+                    lists.push(if name == "ol" { Some(0) } else { None });
+                }
+            }
+            "li" => {
+                writer.end_block();
+                if !closing {
+                    if let Some(Some(count)) = lists.last_mut() {
+                        *count += 1;
+                        writer.text(&format!("{}, ", capitalize(&ordinal_words(*count, false))));
+                    }
+                }
+            }
+            "td" | "th" if closing => writer.text(", "),
+            name if BLOCK_TAGS.contains(&name) => writer.end_block(),
+            _ => {}
+        }
+    }
+
+    writer.finish()
+}
+
+// index just past the closing '>' of the tag at the start of `rest`, ignoring '>' inside quotes
+// a `<` starts a tag only before a letter, `/`, `!`, or `?`; any other `<` is text
+fn tag_start(html: &str) -> Option<usize> {
+    html.match_indices('<').map(|(i, _)| i).find(|&i| {
+        html[i + 1..].starts_with(|c: char| c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?'))
+    })
+}
+
+fn tag_end(rest: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    for (i, c) in rest.char_indices().skip(1) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+pub(crate) fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| {
+                let entity = &rest[1..end + 1];
+                decode_entity(entity).map(|c| (c, end + 2))
+            });
+        match decoded {
+            Some((c, consumed)) => {
+                out.push(c);
+                rest = &rest[consumed..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(number) = entity.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+
+    let c = match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "euro" => '€',
+        "pound" => '£',
+        _ => return None,
+    };
+    Some(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_input() {
+        assert_eq!(html_to_speech_text(""), "");
+    }
+
+    #[test]
+    fn unclosed_tag_before_non_ascii_text() {
+        assert_eq!(html_to_speech_text("< 1 €"), "< 1 €.");
+        assert_eq!(html_to_speech_text("Price: 5 € <b"), "Price: 5 €.");
+        assert_eq!(html_to_speech_text("<p>Café <a href=\"x>y"), "Café.");
+    }
+
+    #[test]
+    fn stray_angle_brackets_are_text() {
+        assert_eq!(
+            html_to_speech_text("<p>if a < b, then 2<3</p>"),
+            "if a < b, then 2<3."
+        );
+    }
+
+    #[test]
+    fn blocks_lists_and_skipped_tags() {
+        let html = "<h1>Menu</h1><script>alert(1)</script><ol><li>Soup</li><li>Bread</li></ol>";
+        assert_eq!(
+            html_to_speech_text(html),
+            "Menu.\n\nFirst, Soup.\n\nSecond, Bread."
+        );
+    }
+
+    #[test]
+    fn quoted_angle_bracket_stays_inside_tag() {
+        assert_eq!(html_to_speech_text("<a title=\"a > b\">link</a>"), "link.");
+    }
+
+    #[test]
+    fn entities() {
+        assert_eq!(
+            decode_entities("Fish &amp; chips &euro;5"),
+            "Fish & chips €5"
+        );
+        assert_eq!(decode_entities("&#233;&#x41;"), "éA");
+        assert_eq!(decode_entities("AT&T &unknown; &"), "AT&T &unknown; &");
+    }
+}
@@ -0,0 +1,184 @@
+const ID3_HEADER_LEN: usize = 10;
+const ENCODING_UTF8: u8 = 0x03;
+
+#[derive(Debug, Clone, Default)]
+pub struct Id3Tags {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub track: Option<u32>,
+    pub comment: Option<String>,
+}
+
+impl Id3Tags {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn artist(mut self, artist: impl Into<String>) -> Self {
+        self.artist = Some(artist.into());
+        self
+    }
+
+    pub fn album(mut self, album: impl Into<String>) -> Self {
+        self.album = Some(album.into());
+        self
+    }
+
+    pub fn track(mut self, track: u32) -> Self {
+        self.track = Some(track);
+        self
+    }
+
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    // ID3v2.4 tag with UTF-8 text frames
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut frames = Vec::new();
+
+        if let Some(ref title) = self.title {
+            write_text_frame(&mut frames, b"TIT2", title);
+        }
+        if let Some(ref artist) = self.artist {
+            write_text_frame(&mut frames, b"TPE1", artist);
+        }
+        if let Some(ref album) = self.album {
+            write_text_frame(&mut frames, b"TALB", album);
+        }
+        if let Some(track) = self.track {
+            write_text_frame(&mut frames, b"TRCK", &track.to_string());
+        }
+        if let Some(ref comment) = self.comment {
+            let mut data = vec![ENCODING_UTF8];
+            data.extend_from_slice(b"eng");
+            data.push(0);
+            data.extend_from_slice(comment.as_bytes());
+            write_frame(&mut frames, b"COMM", &data);
+        }
+
+        let mut tag = Vec::with_capacity(ID3_HEADER_LEN + frames.len());
+        tag.extend_from_slice(b"ID3");
+        tag.extend_from_slice(&[0x04, 0x00, 0x00]);
+        tag.extend_from_slice(&syncsafe(frames.len() as u32));
+        tag.extend_from_slice(&frames);
+        tag
+    }
+
+    pub fn embed(&self, audio: &[u8]) -> Vec<u8> {
+        let audio = strip_id3v2(audio);
+        let mut out = self.to_bytes();
+        out.extend_from_slice(audio);
+        out
+    }
+}
+
+fn write_text_frame(out: &mut Vec<u8>, id: &[u8; 4], text: &str) {
+    let mut data = Vec::with_capacity(text.len() + 1);
+    data.push(ENCODING_UTF8);
+    data.extend_from_slice(text.as_bytes());
+    write_frame(out, id, &data);
+}
+
+fn write_frame(out: &mut Vec<u8>, id: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(id);
+    out.extend_from_slice(&syncsafe(data.len() as u32));
+    out.extend_from_slice(&[0x00, 0x00]);
+    out.extend_from_slice(data);
+}
+
+fn syncsafe(value: u32) -> [u8; 4] {
+    [
+        ((value >> 21) & 0x7f) as u8,
+        ((value >> 14) & 0x7f) as u8,
+        ((value >> 7) & 0x7f) as u8,
+        (value & 0x7f) as u8,
+    ]
+}
+
+fn strip_id3v2(audio: &[u8]) -> &[u8] {
+    if audio.len() < ID3_HEADER_LEN || &audio[..3] != b"ID3" {
+        return audio;
+    }
+
+    let size = audio[6..10]
+        .iter()
+        .fold(0usize, |acc, b| (acc << 7) | (*b & 0x7f) as usize);
+    let has_footer = audio[5] & 0x10 != 0;
+    let end = ID3_HEADER_LEN + size + if has_footer { ID3_HEADER_LEN } else { 0 };
+
+    audio.get(end..).unwrap_or(&[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_tag_is_a_bare_header() {
+        assert_eq!(
+            Id3Tags::new().to_bytes(),
+            [b'I', b'D', b'3', 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]
+        );
+    }
+
+    #[test]
+    fn text_frame_layout() {
+        let bytes = Id3Tags::new().title("Café").to_bytes();
+        // header size covers the frame: 10 header bytes plus the encoding byte and text
+        assert_eq!(
+            &bytes[..10],
+            &[b'I', b'D', b'3', 0x04, 0x00, 0x00, 0, 0, 0, 16]
+        );
+        assert_eq!(&bytes[10..14], b"TIT2");
+        assert_eq!(&bytes[14..18], &[0, 0, 0, 6]);
+        assert_eq!(&bytes[18..20], &[0x00, 0x00]);
+        assert_eq!(bytes[20], ENCODING_UTF8);
+        assert_eq!(&bytes[21..], "Café".as_bytes());
+    }
+
+    #[test]
+    fn comment_frame_layout() {
+        let bytes = Id3Tags::new().comment("id").to_bytes();
+        assert_eq!(&bytes[10..14], b"COMM");
+        assert_eq!(&bytes[14..18], &[0, 0, 0, 7]);
+        assert_eq!(
+            &bytes[20..],
+            &[ENCODING_UTF8, b'e', b'n', b'g', 0, b'i', b'd']
+        );
+    }
+
+    #[test]
+    fn sizes_are_syncsafe() {
+        assert_eq!(syncsafe(127), [0, 0, 0, 0x7f]);
+        assert_eq!(syncsafe(200), [0, 0, 0x01, 0x48]);
+        assert_eq!(syncsafe(0x0fff_ffff), [0x7f, 0x7f, 0x7f, 0x7f]);
+    }
+
+    #[test]
+    fn embed_replaces_an_existing_tag() {
+        let audio = [0xff, 0xfb, 0x90, 0x00];
+        let tagged = Id3Tags::new().title("One").embed(&audio);
+        let retagged = Id3Tags::new().title("Two").embed(&tagged);
+        assert_eq!(retagged, Id3Tags::new().title("Two").embed(&audio));
+        assert!(retagged.ends_with(&audio));
+    }
+
+    #[test]
+    fn strip_handles_short_and_truncated_input() {
+        assert_eq!(strip_id3v2(b""), b"");
+        assert_eq!(strip_id3v2(b"ID3"), b"ID3");
+        // a header claiming more bytes than follow leaves no audio
+        assert_eq!(
+            strip_id3v2(&[b'I', b'D', b'3', 0x04, 0x00, 0x00, 0, 0, 0x01, 0x00, 0xff]),
+            b""
+        );
+    }
+}
@@ -1,9 +1,99 @@
+pub mod account;
+pub mod aliases;
+pub mod audio;
+pub mod audiobook;
+pub mod billing;
+pub mod cache;
 pub mod client;
+pub mod compare;
+#[cfg(feature = "compression")]
+pub mod compress;
+pub mod degradation;
+pub mod delivery;
+pub mod dialogue;
+pub mod document;
 pub mod error;
+pub mod events;
+pub mod export;
+pub mod failover;
+pub mod glossary;
+pub mod hash;
+pub mod html;
+pub mod id3;
+pub mod offline;
+pub mod pacing;
+pub mod pipeline;
+pub mod pool;
+pub mod preset_library;
+pub mod presets;
+pub mod profanity;
+pub mod pronunciation;
+pub mod pronunciation_suite;
+pub mod redact;
+pub mod repro;
+pub mod request_presets;
+pub mod retry;
+pub mod sandbox;
+pub mod session;
+pub mod signed_url;
+pub mod ssml;
+pub mod sweep;
+pub mod telephony;
+pub mod text;
 pub mod types;
+mod util;
+pub mod verbalize;
+pub mod viseme;
+pub mod voice_chat;
+pub mod wav;
+pub mod webhook;
 
+pub use account::{AccountCapabilities, ModelInfo, Subscription};
+pub use aliases::VoiceAliases;
+pub use audio::ResampleQuality;
+pub use audiobook::{AudiobookBuilder, AudiobookManifest, Chapter, ChapterManifest};
+pub use billing::billable_characters;
+pub use cache::CacheStats;
 pub use client::{SpeechClient, SpeechRequestBuilder};
+pub use compare::{ComparisonResult, ComparisonVariant};
+#[cfg(feature = "compression")]
+pub use compress::CompressedAudio;
+pub use degradation::{DegradationPolicy, QuotaThreshold, DEGRADED_TAG};
+pub use delivery::DeliveryPresets;
+pub use dialogue::{DialogueAudio, DialogueLine, DialogueLineTiming, DialogueScript};
+pub use document::{DocumentBuilder, DocumentManifest, SegmentManifest, Segmentation};
 pub use error::TtsError;
+pub use events::RequestEvent;
+pub use export::CorpusExporter;
+pub use failover::{FailoverStats, RegionFailover};
+pub use glossary::{Glossary, GlossaryStore};
+pub use id3::Id3Tags;
+pub use offline::{FlushedRequest, OfflineQueue, QueuedRequest};
+pub use pacing::{PacedSink, Pacer};
+pub use pipeline::{
+    AudioSink, Filler, InterruptHandle, PipelineReport, SpeechPipeline, StreamSource, TextSource,
+};
+pub use pool::{ClientPool, PoolRoute};
+pub use preset_library::PresetLibrary;
+pub use presets::VoicePresets;
+pub use profanity::{ProfanityAction, ProfanityFilter};
+pub use pronunciation::{PronunciationRule, PronunciationRules};
+pub use pronunciation_suite::{PronunciationCase, PronunciationReport, PronunciationSuite};
+pub use redact::{PiiDetector, PiiRedactor};
+pub use repro::{ReplayReport, ReproBundle};
+pub use request_presets::{RequestPreset, RequestPresets};
+pub use retry::{ClassRetry, RetryClass, RetryPolicy, Retryable};
+pub use sandbox::{Sandbox, SandboxAudio};
+pub use session::SpeechSession;
+pub use signed_url::UrlSigner;
+pub use ssml::{translate_ssml, SsmlText};
+pub use sweep::{SweepBuilder, SweepEntry, SweepIndex};
+pub use telephony::{MediaStreamFormatter, UlawFrame, UlawFramer};
+pub use text::SentenceBuffer;
 pub use types::{
-    AudioFormat, SpeechRequest, SpeechResponse, TextNormalization, TtsModel, Voice, VoiceSettings,
+    Alignment, AudioFormat, HedgeConfig, InputLengthPolicy, RawResponse, SpeechRequest,
+    SpeechResponse, TextNormalization, TtsModel, Voice, VoiceSettings,
 };
+pub use viseme::{VisemeCue, VisemeSet};
+pub use wav::{CueGranularity, WavCue};
+pub use webhook::{WebhookEvent, WebhookHandler};
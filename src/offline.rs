@@ -0,0 +1,189 @@
+use crate::error::TtsError;
+use crate::types::{
+    AudioFormat, SpeechRequest, SpeechResponse, TextNormalization, TtsModel, Voice, VoiceSettings,
+};
+use crate::util::{load_json_or_default, now_ms, write_file};
+use hyperware_process_lib::vfs;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+// a request as it was about to be sent: aliases, presets, and deliveries resolved and the
+// text preprocessed, so replaying it does not depend on the client's configuration then
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedRequest {
+    pub id: String,
+    pub queued_at_ms: u64,
+    pub voice_id: String,
+    pub text: String,
+    pub model: TtsModel,
+    pub voice_settings: Option<VoiceSettings>,
+    pub output_format: Option<AudioFormat>,
+    pub language_code: Option<String>,
+    pub seed: Option<u32>,
+    pub previous_text: Option<String>,
+    pub next_text: Option<String>,
+    pub previous_request_ids: Option<Vec<String>>,
+    pub next_request_ids: Option<Vec<String>>,
+    pub apply_text_normalization: Option<TextNormalization>,
+    pub apply_language_text_normalization: Option<bool>,
+    pub with_timestamps: bool,
+    pub extra_fields: serde_json::Map<String, serde_json::Value>,
+    pub query_params: Vec<(String, String)>,
+    pub tags: BTreeMap<String, String>,
+}
+
+impl QueuedRequest {
+    // ID3 tags and hedging are not persisted
+    fn capture(id: String, request: &SpeechRequest) -> Self {
+        Self {
+            id,
+            queued_at_ms: now_ms(),
+            voice_id: request.voice.as_voice_id().to_string(),
+            text: request.text.clone(),
+            model: request.model.clone(),
+            voice_settings: request.voice_settings.clone(),
+            output_format: request.output_format.clone(),
+            language_code: request.language_code.clone(),
+            seed: request.seed,
+            previous_text: request.previous_text.clone(),
+            next_text: request.next_text.clone(),
+            previous_request_ids: request.previous_request_ids.clone(),
+            next_request_ids: request.next_request_ids.clone(),
+            apply_text_normalization: request.apply_text_normalization.clone(),
+            apply_language_text_normalization: request.apply_language_text_normalization,
+            with_timestamps: request.with_timestamps,
+            extra_fields: request.extra_fields.clone(),
+            query_params: request.query_params.clone(),
+            tags: request.tags.clone(),
+        }
+    }
+
+    pub(crate) fn to_request(&self) -> SpeechRequest {
+        SpeechRequest {
+            text: self.text.clone(),
+            model: self.model.clone(),
+            voice: Voice::from_voice_id(&self.voice_id),
+            voice_settings: self.voice_settings.clone(),
+            output_format: self.output_format.clone(),
+            language_code: self.language_code.clone(),
+            seed: self.seed,
+            previous_text: self.previous_text.clone(),
+            next_text: self.next_text.clone(),
+            previous_request_ids: self.previous_request_ids.clone(),
+            next_request_ids: self.next_request_ids.clone(),
+            apply_text_normalization: self.apply_text_normalization.clone(),
+            apply_language_text_normalization: self.apply_language_text_normalization,
+            with_timestamps: self.with_timestamps,
+            extra_fields: self.extra_fields.clone(),
+            query_params: self.query_params.clone(),
+            tags: self.tags.clone(),
+            ..SpeechRequest::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FlushedRequest {
+    pub id: String,
+    // the VFS path the audio was written to
+    pub result: Result<String, TtsError>,
+}
+
+// requests that failed for lack of connectivity, persisted in `{directory}/queue.json`;
+// audio from flushed requests is written to `{directory}/{id}.{ext}` with a `{id}.json`
+// sidecar holding the response metadata, or the error for a request that was rejected
+pub struct OfflineQueue {
+    directory: String,
+    flushing: AtomicBool,
+    counter: AtomicU64,
+}
+
+impl OfflineQueue {
+    pub fn open(directory: impl Into<String>) -> Result<Self, TtsError> {
+        let directory = directory.into().trim_end_matches('/').to_string();
+        vfs::open_dir(&directory, true, None).map_err(|e| TtsError::Vfs(e.to_string()))?;
+        Ok(Self {
+            directory,
+            flushing: AtomicBool::new(false),
+            counter: AtomicU64::new(0),
+        })
+    }
+
+    pub fn pending(&self) -> Result<Vec<QueuedRequest>, TtsError> {
+        load_json_or_default(&self.queue_path())
+    }
+
+    pub(crate) fn enqueue(&self, request: &SpeechRequest) -> Result<String, TtsError> {
+        let id = format!(
+            "{}-{}",
+            now_ms(),
+            self.counter.fetch_add(1, Ordering::Relaxed)
+        );
+        let mut pending = self.pending()?;
+        pending.push(QueuedRequest::capture(id.clone(), request));
+        self.save(&pending)?;
+        Ok(id)
+    }
+
+    // returns false when a flush is already running
+    pub(crate) fn begin_flush(&self) -> bool {
+        !self.flushing.swap(true, Ordering::AcqRel)
+    }
+
+    pub(crate) fn end_flush(&self) {
+        self.flushing.store(false, Ordering::Release);
+    }
+
+    pub(crate) fn complete(
+        &self,
+        queued: &QueuedRequest,
+        response: &SpeechResponse,
+    ) -> Result<String, TtsError> {
+        let path = format!(
+            "{}/{}.{}",
+            self.directory,
+            queued.id,
+            response.format.extension()
+        );
+        write_file(&path, &response.audio_data)?;
+
+        let metadata = json!({
+            "id": queued.id,
+            "queued_at_ms": queued.queued_at_ms,
+            "request_id": response.request_id,
+            "audio_sha256": response.audio_sha256,
+            "model": response.model.as_str(),
+            "voice_id": response.voice.as_voice_id(),
+            "output_format": response.format.as_str(),
+            "tags": response.tags,
+            "warnings": response.warnings,
+        });
+        let bytes = serde_json::to_vec_pretty(&metadata)
+            .map_err(|e| TtsError::SerializationError(e.to_string()))?;
+        write_file(&format!("{}/{}.json", self.directory, queued.id), &bytes)?;
+        Ok(path)
+    }
+
+    pub(crate) fn fail(&self, queued: &QueuedRequest, error: &TtsError) -> Result<(), TtsError> {
+        let metadata = json!({
+            "id": queued.id,
+            "queued_at_ms": queued.queued_at_ms,
+            "error": error.to_string(),
+        });
+        let bytes = serde_json::to_vec_pretty(&metadata)
+            .map_err(|e| TtsError::SerializationError(e.to_string()))?;
+        write_file(&format!("{}/{}.json", self.directory, queued.id), &bytes)
+    }
+
+    pub(crate) fn save(&self, pending: &[QueuedRequest]) -> Result<(), TtsError> {
+        let bytes = serde_json::to_vec_pretty(pending)
+            .map_err(|e| TtsError::SerializationError(e.to_string()))?;
+        write_file(&self.queue_path(), &bytes)
+    }
+
+    fn queue_path(&self) -> String {
+        format!("{}/queue.json", self.directory)
+    }
+}
@@ -0,0 +1,165 @@
+use crate::audio::{mp3_parameters, pcm_sample_rate};
+use crate::error::TtsError;
+use crate::pipeline::AudioSink;
+use crate::types::{AudioFormat, SpeechResponse};
+use futures::stream::{self, Stream, StreamExt};
+use hyperware_process_lib::hyperapp::sleep;
+use std::time::Instant;
+
+const DEFAULT_JITTER_BUFFER_MS: u64 = 60;
+const DEFAULT_CHUNK_MS: u64 = 20;
+
+// releases audio at the rate it plays, keeping `jitter_buffer_ms` of it ahead of the listener
+// so a late chunk from upstream doesn't cause a gap. After an underrun (everything released
+// has played out) the clock restarts, and the buffer refills before pacing resumes, rather
+// than bursting to catch up.
+#[derive(Debug, Clone)]
+pub struct Pacer {
+    format: AudioFormat,
+    jitter_buffer_ms: u64,
+    started: Option<Instant>,
+    released_bytes: usize,
+    underruns: usize,
+}
+
+impl Pacer {
+    pub fn new(format: AudioFormat) -> Self {
+        Self {
+            format,
+            jitter_buffer_ms: DEFAULT_JITTER_BUFFER_MS,
+            started: None,
+            released_bytes: 0,
+            underruns: 0,
+        }
+    }
+
+    pub fn jitter_buffer_ms(mut self, jitter_buffer_ms: u64) -> Self {
+        self.jitter_buffer_ms = jitter_buffer_ms;
+        self
+    }
+
+    pub fn format(&self) -> &AudioFormat {
+        &self.format
+    }
+
+    pub fn underruns(&self) -> usize {
+        self.underruns
+    }
+
+    // waits until a chunk of `len` bytes is due, then counts it as released
+    pub async fn wait(&mut self, len: usize) {
+        let now = Instant::now();
+        let started = *self.started.get_or_insert(now);
+        let elapsed_ms = now.duration_since(started).as_millis() as u64;
+        let released_ms = self.format.estimated_duration_ms(self.released_bytes);
+
+        if self.released_bytes > 0 && elapsed_ms > released_ms {
+            self.underruns += 1;
+            self.started = Some(now);
+            self.released_bytes = 0;
+        } else {
+            let due_ms = released_ms.saturating_sub(self.jitter_buffer_ms);
+            if due_ms > elapsed_ms {
+                let _ = sleep(due_ms - elapsed_ms).await;
+            }
+        }
+        self.released_bytes += len;
+    }
+
+    // for a new utterance after a pause, so the pause isn't counted as an underrun
+    pub fn reset(&mut self) {
+        self.started = None;
+        self.released_bytes = 0;
+    }
+}
+
+// yields the stream's chunks no faster than they play
+pub fn pace<S>(chunks: S, pacer: Pacer) -> impl Stream<Item = Vec<u8>>
+where
+    S: Stream<Item = Vec<u8>>,
+{
+    stream::unfold(
+        (Box::pin(chunks), pacer),
+        |(mut chunks, mut pacer)| async move {
+            let chunk = chunks.next().await?;
+            pacer.wait(chunk.len()).await;
+            Some((chunk, (chunks, pacer)))
+        },
+    )
+}
+
+// splits audio into pieces of about `chunk_ms` each; mp3 is split by bitrate, so pieces may
+// cut through frames, which streaming decoders tolerate
+pub fn split_chunks(audio: &[u8], format: &AudioFormat, chunk_ms: u64) -> Vec<Vec<u8>> {
+    let len = chunk_len(format, chunk_ms.max(1));
+    audio.chunks(len).map(<[u8]>::to_vec).collect()
+}
+
+fn chunk_len(format: &AudioFormat, chunk_ms: u64) -> usize {
+    let bytes_per_second = match (pcm_sample_rate(format), mp3_parameters(format)) {
+        (Some(rate), _) => rate as u64 * 2,
+        (None, Some((_, bitrate))) => bitrate as u64 * 1000 / 8,
+        // µ-law, one byte per sample at 8 kHz
+        (None, None) => 8000,
+    };
+    ((bytes_per_second * chunk_ms / 1000) as usize).max(1)
+}
+
+// an AudioSink that passes each response on in `chunk_ms` pieces at real-time rate, for
+// senders with no clock of their own. Pieces carry the response's metadata, with
+// `audio_sha256` still that of the whole response, and no alignment.
+pub struct PacedSink<A> {
+    inner: A,
+    pacer: Pacer,
+    chunk_ms: u64,
+}
+
+impl<A: AudioSink> PacedSink<A> {
+    pub fn new(inner: A, pacer: Pacer) -> Self {
+        Self {
+            inner,
+            pacer,
+            chunk_ms: DEFAULT_CHUNK_MS,
+        }
+    }
+
+    pub fn chunk_ms(mut self, chunk_ms: u64) -> Self {
+        self.chunk_ms = chunk_ms;
+        self
+    }
+
+    pub fn pacer(&self) -> &Pacer {
+        &self.pacer
+    }
+
+    pub fn into_inner(self) -> A {
+        self.inner
+    }
+}
+
+impl<A: AudioSink> AudioSink for PacedSink<A> {
+    async fn write(&mut self, response: &SpeechResponse) -> Result<(), TtsError> {
+        let template = SpeechResponse {
+            audio_data: Vec::new(),
+            audio_sha256: response.audio_sha256.clone(),
+            format: response.format.clone(),
+            model: response.model.clone(),
+            voice: response.voice.clone(),
+            request_id: response.request_id.clone(),
+            repro_bundle: response.repro_bundle.clone(),
+            tags: response.tags.clone(),
+            warnings: response.warnings.clone(),
+            alignment: None,
+            normalized_alignment: None,
+        };
+        for chunk in split_chunks(&response.audio_data, self.pacer.format(), self.chunk_ms) {
+            self.pacer.wait(chunk.len()).await;
+            let piece = SpeechResponse {
+                audio_data: chunk,
+                ..template.clone()
+            };
+            self.inner.write(&piece).await?;
+        }
+        Ok(())
+    }
+}
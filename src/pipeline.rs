@@ -0,0 +1,229 @@
+use crate::audio::{crossfade_pcm, pcm_sample_rate};
+use crate::error::TtsError;
+use crate::session::SpeechSession;
+use crate::text::SentenceBuffer;
+use crate::types::SpeechResponse;
+use futures::future::{select, Either};
+use futures::stream::{Stream, StreamExt};
+use hyperware_process_lib::hyperapp::sleep;
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+const DEFAULT_CROSSFADE_MS: u64 = 40;
+
+// where a reply's text comes from, e.g. an LLM provider's token stream
+pub trait TextSource {
+    // the next piece of the reply, or None once it is complete
+    fn next_text(&mut self) -> impl Future<Output = Option<String>>;
+}
+
+// where synthesized audio goes, e.g. a telephony channel or a file
+pub trait AudioSink {
+    fn write(&mut self, response: &SpeechResponse) -> impl Future<Output = Result<(), TtsError>>;
+}
+
+// adapts any stream of text to a TextSource
+pub struct StreamSource<S> {
+    stream: Pin<Box<S>>,
+}
+
+impl<S: Stream<Item = String>> StreamSource<S> {
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream: Box::pin(stream),
+        }
+    }
+}
+
+impl<S: Stream<Item = String>> TextSource for StreamSource<S> {
+    async fn next_text(&mut self) -> Option<String> {
+        self.stream.next().await
+    }
+}
+
+// collects the audio of every response
+impl AudioSink for Vec<u8> {
+    async fn write(&mut self, response: &SpeechResponse) -> Result<(), TtsError> {
+        self.extend_from_slice(&response.audio_data);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PipelineReport {
+    pub utterances: usize,
+    pub characters: usize,
+    // the turn was cut short; buffered text that had not been sent was dropped
+    pub interrupted: bool,
+    // the first audio was late, so the filler played ahead of it
+    pub filler_played: bool,
+}
+
+// pre-synthesized audio ("hmm, let me see...") played when a turn's first audio hasn't
+// arrived `after_ms` after the turn started. Pcm fillers crossfade into the response when it
+// arrives in the same format; other formats play whole, followed by the response.
+#[derive(Debug, Clone)]
+pub struct Filler {
+    audio: SpeechResponse,
+    after_ms: u64,
+    crossfade_ms: u64,
+}
+
+impl Filler {
+    pub fn new(audio: SpeechResponse, after_ms: u64) -> Self {
+        Self {
+            audio,
+            after_ms,
+            crossfade_ms: DEFAULT_CROSSFADE_MS,
+        }
+    }
+
+    pub fn crossfade_ms(mut self, crossfade_ms: u64) -> Self {
+        self.crossfade_ms = crossfade_ms;
+        self
+    }
+
+    // the audio to play straight away, and the tail held back to crossfade into the response
+    fn split(&self) -> (SpeechResponse, Vec<u8>) {
+        let mut head = self.audio.clone();
+        let tail_len = pcm_sample_rate(&head.format)
+            .map(|rate| (rate as u64 * self.crossfade_ms / 1000) as usize * 2)
+            .unwrap_or(0)
+            .min(head.audio_data.len() & !1);
+        let tail = head.audio_data.split_off(head.audio_data.len() - tail_len);
+        (head, tail)
+    }
+}
+
+// stops the running turn before its next request, e.g. when the user starts talking
+#[derive(Debug, Clone)]
+pub struct InterruptHandle {
+    interrupted: Arc<AtomicBool>,
+}
+
+impl InterruptHandle {
+    pub fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::Release);
+    }
+}
+
+// connects a text source to an audio sink through a session, so context carries across the
+// utterances of a turn and across turns
+pub struct SpeechPipeline<'a> {
+    session: SpeechSession<'a>,
+    buffer: SentenceBuffer,
+    interrupted: Arc<AtomicBool>,
+    filler: Option<Filler>,
+}
+
+impl<'a> SpeechPipeline<'a> {
+    pub fn new(session: SpeechSession<'a>) -> Self {
+        Self {
+            session,
+            buffer: SentenceBuffer::default(),
+            interrupted: Arc::new(AtomicBool::new(false)),
+            filler: None,
+        }
+    }
+
+    pub fn buffer(mut self, buffer: SentenceBuffer) -> Self {
+        self.buffer = buffer;
+        self
+    }
+
+    pub fn filler(mut self, filler: Filler) -> Self {
+        self.filler = Some(filler);
+        self
+    }
+
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        InterruptHandle {
+            interrupted: self.interrupted.clone(),
+        }
+    }
+
+    // runs one turn: reads the source to its end, speaking each buffered sentence group into
+    // the sink as soon as it is released. An interrupt from before the turn started is ignored.
+    pub async fn run<T: TextSource, A: AudioSink>(
+        &mut self,
+        source: &mut T,
+        sink: &mut A,
+    ) -> Result<PipelineReport, TtsError> {
+        self.interrupted.store(false, Ordering::Release);
+        let started = Instant::now();
+        let mut buffer = self.buffer.clone();
+        let mut report = PipelineReport::default();
+
+        loop {
+            let (texts, finished) = match source.next_text().await {
+                Some(text) => (buffer.push(&text), false),
+                None => (buffer.flush().into_iter().collect(), true),
+            };
+            for text in texts {
+                if self.interrupted.load(Ordering::Acquire) {
+                    report.interrupted = true;
+                    return Ok(report);
+                }
+                report.characters += text.chars().count();
+                let response = if report.utterances == 0 {
+                    let (response, filler_played) = self.say_first(text, started, sink).await?;
+                    report.filler_played = filler_played;
+                    response
+                } else {
+                    self.session.say(text).await?
+                };
+                sink.write(&response).await?;
+                report.utterances += 1;
+            }
+            if finished {
+                return Ok(report);
+            }
+            if self.interrupted.load(Ordering::Acquire) {
+                report.interrupted = true;
+                return Ok(report);
+            }
+        }
+    }
+
+    // the turn's first utterance, racing the filler's deadline; the time spent waiting on the
+    // source counts towards it. The flag reports whether the filler played.
+    async fn say_first<A: AudioSink>(
+        &mut self,
+        text: String,
+        started: Instant,
+        sink: &mut A,
+    ) -> Result<(SpeechResponse, bool), TtsError> {
+        let Some(filler) = &self.filler else {
+            return Ok((self.session.say(text).await?, false));
+        };
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        let speech = pin!(self.session.say(text));
+        let deadline = pin!(sleep(filler.after_ms.saturating_sub(elapsed_ms)));
+        let speech = match select(speech, deadline).await {
+            Either::Left((response, _)) => return Ok((response?, false)),
+            Either::Right((_, speech)) => speech,
+        };
+
+        let (head, tail) = filler.split();
+        sink.write(&head).await?;
+        let mut response = speech.await?;
+        if !tail.is_empty() {
+            if response.format == head.format {
+                response.audio_data = crossfade_pcm(&tail, &response.audio_data);
+            } else {
+                let mut tail_response = head;
+                tail_response.audio_data = tail;
+                sink.write(&tail_response).await?;
+            }
+        }
+        Ok((response, true))
+    }
+
+    // forgets the context of earlier turns
+    pub fn reset(&mut self) {
+        self.session.reset();
+    }
+}
@@ -0,0 +1,100 @@
+use crate::client::{SpeechClient, SpeechRequestBuilder};
+use crate::error::TtsError;
+use crate::types::{SpeechRequest, SpeechResponse};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PoolRoute {
+    // the client registered for this tenant; least recently used when several are
+    Tenant(String),
+    Label(String),
+    LeastRecentlyUsed,
+}
+
+struct PoolEntry {
+    label: String,
+    tenants: Vec<String>,
+    client: SpeechClient,
+    last_used: AtomicU64,
+}
+
+// several configured clients (separate accounts, regions, or base URLs) behind one router
+#[derive(Default)]
+pub struct ClientPool {
+    entries: Vec<PoolEntry>,
+    uses: AtomicU64,
+}
+
+impl ClientPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(self, label: impl Into<String>, client: SpeechClient) -> Self {
+        self.add_for_tenants(label, Vec::<String>::new(), client)
+    }
+
+    pub fn add_for_tenants(
+        mut self,
+        label: impl Into<String>,
+        tenants: impl IntoIterator<Item = impl Into<String>>,
+        client: SpeechClient,
+    ) -> Self {
+        self.entries.push(PoolEntry {
+            label: label.into(),
+            tenants: tenants.into_iter().map(Into::into).collect(),
+            client,
+            last_used: AtomicU64::new(0),
+        });
+        self
+    }
+
+    pub fn labels(&self) -> Vec<&str> {
+        self.entries
+            .iter()
+            .map(|entry| entry.label.as_str())
+            .collect()
+    }
+
+    pub fn client(&self, label: &str) -> Option<&SpeechClient> {
+        self.entries
+            .iter()
+            .find(|entry| entry.label == label)
+            .map(|entry| &entry.client)
+    }
+
+    // picks a client and marks it used, so least-recently-used routing spreads later requests
+    pub fn route(&self, route: &PoolRoute) -> Result<&SpeechClient, TtsError> {
+        let candidates = self.entries.iter().filter(|entry| match route {
+            PoolRoute::Tenant(tenant) => entry.tenants.contains(tenant),
+            PoolRoute::Label(label) => entry.label == *label,
+            PoolRoute::LeastRecentlyUsed => true,
+        });
+        let entry = candidates
+            .min_by_key(|entry| entry.last_used.load(Ordering::Relaxed))
+            .ok_or_else(|| TtsError::NoPooledClient(describe(route)))?;
+        let tick = self.uses.fetch_add(1, Ordering::Relaxed) + 1;
+        entry.last_used.store(tick, Ordering::Relaxed);
+        Ok(&entry.client)
+    }
+
+    pub async fn send(
+        &self,
+        route: &PoolRoute,
+        request: SpeechRequest,
+    ) -> Result<SpeechResponse, TtsError> {
+        self.route(route)?.send(request).await
+    }
+
+    pub fn synthesize(&self, route: &PoolRoute) -> Result<SpeechRequestBuilder, TtsError> {
+        Ok(self.route(route)?.synthesize())
+    }
+}
+
+fn describe(route: &PoolRoute) -> String {
+    match route {
+        PoolRoute::Tenant(tenant) => format!("tenant {:?}", tenant),
+        PoolRoute::Label(label) => format!("label {:?}", label),
+        PoolRoute::LeastRecentlyUsed => "an empty pool".to_string(),
+    }
+}
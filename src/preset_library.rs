@@ -0,0 +1,70 @@
+use crate::error::TtsError;
+use crate::request_presets::{RequestPreset, RequestPresets};
+use crate::util::{load_json_or_default, write_file};
+use hyperware_process_lib::vfs;
+
+// request presets stored one per file as `{directory}/{name}.json`; point processes at the
+// same drive path and they all resolve the same presets
+pub struct PresetLibrary {
+    directory: String,
+}
+
+impl PresetLibrary {
+    pub fn open(directory: impl Into<String>) -> Result<Self, TtsError> {
+        let directory = directory.into().trim_end_matches('/').to_string();
+        vfs::open_dir(&directory, true, None).map_err(|e| TtsError::Vfs(e.to_string()))?;
+        Ok(Self { directory })
+    }
+
+    pub fn save(&self, name: &str, preset: &RequestPreset) -> Result<(), TtsError> {
+        let bytes = serde_json::to_vec_pretty(preset)
+            .map_err(|e| TtsError::SerializationError(e.to_string()))?;
+        write_file(&self.path(name)?, &bytes)
+    }
+
+    // saves every preset in the document, replacing presets of the same name
+    pub fn import(&self, presets: &RequestPresets) -> Result<(), TtsError> {
+        for name in presets.names() {
+            if let Some(preset) = presets.get(name) {
+                self.save(name, preset)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Result<RequestPreset, TtsError> {
+        load_json_or_default::<Option<RequestPreset>>(&self.path(name)?)?
+            .ok_or_else(|| TtsError::UnknownRequestPreset(name.to_string()))
+    }
+
+    pub fn list(&self) -> Result<Vec<String>, TtsError> {
+        let dir = vfs::open_dir(&self.directory, false, None)
+            .map_err(|e| TtsError::Vfs(e.to_string()))?;
+        let entries = dir.read().map_err(|e| TtsError::Vfs(e.to_string()))?;
+        let mut names: Vec<String> = entries
+            .iter()
+            .filter_map(|entry| {
+                let file_name = entry.path.rsplit('/').next()?;
+                file_name.strip_suffix(".json").map(str::to_string)
+            })
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    pub fn remove(&self, name: &str) -> Result<(), TtsError> {
+        vfs::remove_file(&self.path(name)?, None).map_err(|e| TtsError::Vfs(e.to_string()))
+    }
+
+    // names become file names, so anything that could reach outside the directory is refused
+    fn path(&self, name: &str) -> Result<String, TtsError> {
+        if name.is_empty()
+            || name.contains('/')
+            || name.contains("..")
+            || name.chars().any(char::is_control)
+        {
+            return Err(TtsError::InvalidPresetName(name.to_string()));
+        }
+        Ok(format!("{}/{}.json", self.directory, name))
+    }
+}
@@ -0,0 +1,42 @@
+use crate::error::TtsError;
+use crate::types::{Voice, VoiceSettings};
+use crate::util::kv_get;
+use hyperware_process_lib::kv::{self, Kv};
+use hyperware_process_lib::our;
+
+pub struct VoicePresets {
+    kv: Kv<String, VoiceSettings>,
+}
+
+impl VoicePresets {
+    // presets live in a KV database owned by the calling package, shared by all of its processes
+    pub fn open(db: &str) -> Result<Self, TtsError> {
+        let kv = kv::open(our().package_id(), db, None).map_err(|e| TtsError::Kv(e.to_string()))?;
+        Ok(Self { kv })
+    }
+
+    pub fn save(
+        &self,
+        voice: &Voice,
+        name: &str,
+        settings: &VoiceSettings,
+    ) -> Result<(), TtsError> {
+        self.kv
+            .set(&preset_key(voice, name), settings, None)
+            .map_err(|e| TtsError::Kv(e.to_string()))
+    }
+
+    pub fn get(&self, voice: &Voice, name: &str) -> Option<VoiceSettings> {
+        kv_get(&self.kv, &preset_key(voice, name))
+    }
+
+    pub fn delete(&self, voice: &Voice, name: &str) -> Result<(), TtsError> {
+        self.kv
+            .delete(&preset_key(voice, name), None)
+            .map_err(|e| TtsError::Kv(e.to_string()))
+    }
+}
+
+fn preset_key(voice: &Voice, name: &str) -> String {
+    format!("{}/{}", voice.as_voice_id(), name)
+}
@@ -0,0 +1,171 @@
+use crate::error::TtsError;
+use hyperware_process_lib::vfs;
+use regex::{Captures, Regex};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ProfanityAction {
+    // replaces every character of a listed word with '*', which is not spoken
+    #[default]
+    Mask,
+    // fails the request with TtsError::BlockedTerm before anything is sent
+    Reject,
+    Replace(String),
+}
+
+// matches listed words case-insensitively, as whole words only: a listed term never matches
+// inside a longer word, including terms that start or end with symbols, like "a$$"
+#[derive(Debug, Clone)]
+pub struct ProfanityFilter {
+    words: Vec<String>,
+    pattern: Option<Regex>,
+    action: ProfanityAction,
+}
+
+impl ProfanityFilter {
+    // fails rather than filtering less than listed, e.g. when the list is too large to compile
+    pub fn new<I, S>(words: I) -> Result<Self, TtsError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut filter = Self {
+            words: Vec::new(),
+            pattern: None,
+            action: ProfanityAction::default(),
+        };
+        filter.extend(words)?;
+        Ok(filter)
+    }
+
+    // one word or phrase per line; blank lines and lines starting with '#' are skipped
+    pub fn from_vfs(path: &str) -> Result<Self, TtsError> {
+        let file = vfs::open_file(path, false, None).map_err(|e| TtsError::Vfs(e.to_string()))?;
+        let bytes = file.read().map_err(|e| TtsError::Vfs(e.to_string()))?;
+        let list = String::from_utf8_lossy(&bytes);
+        Self::new(
+            list.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#')),
+        )
+    }
+
+    pub fn words<I, S>(mut self, words: I) -> Result<Self, TtsError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.extend(words)?;
+        Ok(self)
+    }
+
+    pub fn action(mut self, action: ProfanityAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    pub fn apply(&self, text: &str) -> Result<String, TtsError> {
+        let Some(ref pattern) = self.pattern else {
+            return Ok(text.to_string());
+        };
+        match &self.action {
+            ProfanityAction::Reject => match pattern.find(text) {
+                Some(found) => Err(TtsError::BlockedTerm(found.as_str().to_string())),
+                None => Ok(text.to_string()),
+            },
+            ProfanityAction::Mask => Ok(pattern
+                .replace_all(text, |caps: &Captures| "*".repeat(caps[0].chars().count()))
+                .into_owned()),
+            ProfanityAction::Replace(replacement) => Ok(pattern
+                .replace_all(text, regex::NoExpand(replacement))
+                .into_owned()),
+        }
+    }
+
+    fn extend<I, S>(&mut self, words: I) -> Result<(), TtsError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.words.extend(
+            words
+                .into_iter()
+                .map(Into::into)
+                .filter(|word| !word.trim().is_empty()),
+        );
+        self.words.sort();
+        self.words.dedup();
+        // longest first, so a listed phrase wins over a listed word inside it
+        self.words.sort_by_key(|word| std::cmp::Reverse(word.len()));
+
+        let alternatives: Vec<String> = self.words.iter().map(|w| whole_word(w)).collect();
+        self.pattern = if alternatives.is_empty() {
+            None
+        } else {
+            let pattern = format!("(?i){}", alternatives.join("|"));
+            let pattern =
+                Regex::new(&pattern).map_err(|e| TtsError::InvalidProfanityList(e.to_string()))?;
+            Some(pattern)
+        };
+        Ok(())
+    }
+}
+
+// `\b` only holds next to a word character; an edge of the term that is a symbol takes `\B`
+// instead, which holds when its neighbour is a symbol, whitespace, or the end of the text
+fn whole_word(term: &str) -> String {
+    let anchor = |c: Option<char>| match c {
+        Some(c) if c.is_alphanumeric() || c == '_' => r"\b",
+        _ => r"\B",
+    };
+    format!(
+        "{}{}{}",
+        anchor(term.chars().next()),
+        regex::escape(term),
+        anchor(term.chars().next_back())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_whole_words_only() {
+        let filter = ProfanityFilter::new(["heck"]).unwrap();
+        assert_eq!(
+            filter.apply("Heck, what the heck.").unwrap(),
+            "****, what the ****."
+        );
+        assert_eq!(filter.apply("checking").unwrap(), "checking");
+    }
+
+    #[test]
+    fn terms_with_symbol_edges() {
+        let filter = ProfanityFilter::new(["a$$", "$hit", "f*ck"]).unwrap();
+        assert_eq!(filter.apply("you a$$.").unwrap(), "you ***.");
+        assert_eq!(filter.apply("a$$").unwrap(), "***");
+        assert_eq!(filter.apply("oh $hit").unwrap(), "oh ****");
+        assert_eq!(filter.apply("what the f*ck!").unwrap(), "what the ****!");
+        assert_eq!(filter.apply("a$$et and a$hit").unwrap(), "a$$et and a$hit");
+    }
+
+    #[test]
+    fn longest_term_wins() {
+        let filter = ProfanityFilter::new(["darn", "darn it"])
+            .unwrap()
+            .action(ProfanityAction::Replace("beep".to_string()));
+        assert_eq!(filter.apply("Darn it, darn.").unwrap(), "beep, beep.");
+    }
+
+    #[test]
+    fn reject_and_empty_list() {
+        let filter = ProfanityFilter::new(["heck"])
+            .unwrap()
+            .action(ProfanityAction::Reject);
+        assert!(
+            matches!(filter.apply("oh heck"), Err(TtsError::BlockedTerm(term)) if term == "heck")
+        );
+        let empty = ProfanityFilter::new(Vec::<String>::new()).unwrap();
+        assert_eq!(empty.apply("").unwrap(), "");
+    }
+}
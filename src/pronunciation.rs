@@ -0,0 +1,155 @@
+use crate::error::TtsError;
+use crate::glossary::Glossary;
+use regex::Regex;
+
+#[derive(Debug, Clone)]
+pub enum PronunciationRule {
+    Alias { from: String, to: String },
+    Regex { pattern: Regex, replacement: String },
+}
+
+impl PronunciationRule {
+    fn apply(&self, text: &str) -> String {
+        match self {
+            PronunciationRule::Alias { from, to } => replace_whole_words(text, from, to),
+            PronunciationRule::Regex {
+                pattern,
+                replacement,
+            } => pattern.replace_all(text, replacement.as_str()).into_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PronunciationRules {
+    rules: Vec<(Option<String>, PronunciationRule)>,
+}
+
+impl PronunciationRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn alias(self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.push_alias(None, from, to)
+    }
+
+    pub fn alias_for(
+        self,
+        language: impl Into<String>,
+        from: impl Into<String>,
+        to: impl Into<String>,
+    ) -> Self {
+        self.push_alias(Some(language.into()), from, to)
+    }
+
+    pub fn regex(self, pattern: &str, replacement: impl Into<String>) -> Result<Self, TtsError> {
+        self.push_regex(None, pattern, replacement)
+    }
+
+    pub fn regex_for(
+        self,
+        language: impl Into<String>,
+        pattern: &str,
+        replacement: impl Into<String>,
+    ) -> Result<Self, TtsError> {
+        self.push_regex(Some(language.into()), pattern, replacement)
+    }
+
+    // each term becomes a whole-word alias, applied after the rules added before it
+    pub fn glossary(self, glossary: &Glossary) -> Self {
+        glossary
+            .terms
+            .iter()
+            .fold(self, |rules, (source, spoken)| rules.alias(source, spoken))
+    }
+
+    pub fn rule(mut self, language: Option<String>, rule: PronunciationRule) -> Self {
+        self.rules.push((language, rule));
+        self
+    }
+
+    // rules run in insertion order; language-scoped rules only run for a matching language code
+    pub fn apply(&self, text: &str, language: Option<&str>) -> String {
+        let mut text = text.to_string();
+        for (rule_language, rule) in &self.rules {
+            let applies = match rule_language {
+                Some(rule_language) => language.is_some_and(|l| same_language(rule_language, l)),
+                None => true,
+            };
+            if applies {
+                text = rule.apply(&text);
+            }
+        }
+        text
+    }
+
+    fn push_alias(
+        mut self,
+        language: Option<String>,
+        from: impl Into<String>,
+        to: impl Into<String>,
+    ) -> Self {
+        self.rules.push((
+            language,
+            PronunciationRule::Alias {
+                from: from.into(),
+                to: to.into(),
+            },
+        ));
+        self
+    }
+
+    fn push_regex(
+        mut self,
+        language: Option<String>,
+        pattern: &str,
+        replacement: impl Into<String>,
+    ) -> Result<Self, TtsError> {
+        let pattern =
+            Regex::new(pattern).map_err(|e| TtsError::InvalidPronunciationRule(e.to_string()))?;
+        self.rules.push((
+            language,
+            PronunciationRule::Regex {
+                pattern,
+                replacement: replacement.into(),
+            },
+        ));
+        Ok(self)
+    }
+}
+
+fn same_language(a: &str, b: &str) -> bool {
+    let primary = |code: &str| {
+        code.split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase()
+    };
+    primary(a) == primary(b)
+}
+
+fn replace_whole_words(text: &str, from: &str, to: &str) -> String {
+    if from.is_empty() {
+        return text.to_string();
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = 0;
+
+    for (start, _) in text.match_indices(from) {
+        let end = start + from.len();
+        let before = text[..start].chars().next_back();
+        let after = text[end..].chars().next();
+        let is_word_char = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+
+        if !is_word_char(before) && !is_word_char(after) {
+            out.push_str(&text[rest..start]);
+            out.push_str(to);
+            rest = end;
+        }
+    }
+
+    out.push_str(&text[rest..]);
+    out
+}
@@ -0,0 +1,175 @@
+use crate::client::SpeechClient;
+use crate::types::{SpeechRequest, TtsModel, Voice};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PronunciationCase {
+    pub phrase: String,
+    // the text the pronunciation rules should rewrite the phrase to
+    pub expected_spoken: Option<String>,
+    // recorded with the result for listeners; not checked automatically
+    pub expected_ipa: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PronunciationCaseResult {
+    pub phrase: String,
+    pub sent_text: String,
+    pub expected_spoken: Option<String>,
+    pub expected_ipa: Option<String>,
+    // whether `sent_text` equals `expected_spoken`, when one was given
+    pub rewrite_matches: Option<bool>,
+    pub audio_sha256: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub request_id: Option<String>,
+    pub error: Option<String>,
+    // set by `compare_with`: whether the audio differs from the baseline run's
+    pub audio_changed: Option<bool>,
+}
+
+impl PronunciationCaseResult {
+    pub fn passed(&self) -> bool {
+        self.error.is_none() && self.rewrite_matches != Some(false)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PronunciationReport {
+    pub results: Vec<PronunciationCaseResult>,
+}
+
+impl PronunciationReport {
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(PronunciationCaseResult::passed)
+    }
+
+    pub fn failures(&self) -> Vec<&PronunciationCaseResult> {
+        self.results.iter().filter(|r| !r.passed()).collect()
+    }
+
+    // marks each phrase whose audio hash differs from the same phrase in `baseline`, e.g. a
+    // report saved before a dictionary or model change
+    pub fn compare_with(mut self, baseline: &PronunciationReport) -> Self {
+        for result in &mut self.results {
+            let previous = baseline
+                .results
+                .iter()
+                .find(|r| r.phrase == result.phrase)
+                .and_then(|r| r.audio_sha256.as_ref());
+            result.audio_changed = match (previous, &result.audio_sha256) {
+                (Some(previous), Some(current)) => Some(previous != current),
+                _ => None,
+            };
+        }
+        self
+    }
+}
+
+// a set of phrases to synthesize with the client's pronunciation rules, checking that
+// each is rewritten as expected and recording the audio for review
+#[derive(Debug, Clone, Default)]
+pub struct PronunciationSuite {
+    cases: Vec<PronunciationCase>,
+    template: SpeechRequest,
+}
+
+impl PronunciationSuite {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn case(mut self, phrase: impl Into<String>, expected_spoken: impl Into<String>) -> Self {
+        self.cases.push(PronunciationCase {
+            phrase: phrase.into(),
+            expected_spoken: Some(expected_spoken.into()),
+            expected_ipa: None,
+        });
+        self
+    }
+
+    pub fn case_ipa(mut self, phrase: impl Into<String>, expected_ipa: impl Into<String>) -> Self {
+        self.cases.push(PronunciationCase {
+            phrase: phrase.into(),
+            expected_spoken: None,
+            expected_ipa: Some(expected_ipa.into()),
+        });
+        self
+    }
+
+    pub fn push(mut self, case: PronunciationCase) -> Self {
+        self.cases.push(case);
+        self
+    }
+
+    pub fn voice(mut self, voice: Voice) -> Self {
+        self.template.voice = voice;
+        self
+    }
+
+    pub fn model(mut self, model: TtsModel) -> Self {
+        self.template.model = model;
+        self
+    }
+
+    pub fn language_code(mut self, code: impl Into<String>) -> Self {
+        self.template.language_code = Some(code.into());
+        self
+    }
+
+    pub fn cases(&self) -> &[PronunciationCase] {
+        &self.cases
+    }
+
+    // failed syntheses are recorded on their case rather than ending the run
+    pub async fn run(&self, client: &SpeechClient) -> PronunciationReport {
+        let mut results = Vec::with_capacity(self.cases.len());
+        for case in &self.cases {
+            let mut request = self.template.clone();
+            request.text = case.phrase.clone();
+            // the text the client would send, after the same pipeline as a real request
+            let prepared = client.prepare(request.clone());
+            let sent_text = prepared
+                .as_ref()
+                .map(|prepared| prepared.text.clone())
+                .unwrap_or_default();
+            let rewrite_matches = match prepared {
+                Ok(_) => case
+                    .expected_spoken
+                    .as_ref()
+                    .map(|expected| *expected == sent_text),
+                Err(_) => None,
+            };
+
+            let mut result = PronunciationCaseResult {
+                phrase: case.phrase.clone(),
+                sent_text,
+                expected_spoken: case.expected_spoken.clone(),
+                expected_ipa: case.expected_ipa.clone(),
+                rewrite_matches,
+                audio_sha256: None,
+                duration_ms: None,
+                request_id: None,
+                error: None,
+                audio_changed: None,
+            };
+            if let Err(e) = prepared {
+                result.error = Some(e.to_string());
+                results.push(result);
+                continue;
+            }
+            match client.send_speech_request(request).await {
+                Ok(response) => {
+                    let duration_ms = response
+                        .format
+                        .estimated_duration_ms(response.audio_data.len());
+                    result.duration_ms = Some(duration_ms);
+                    result.audio_sha256 = Some(response.audio_sha256);
+                    result.request_id = response.request_id;
+                }
+                Err(e) => result.error = Some(e.to_string()),
+            }
+            results.push(result);
+        }
+        PronunciationReport { results }
+    }
+}
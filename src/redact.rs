@@ -0,0 +1,298 @@
+use crate::error::TtsError;
+use regex::Regex;
+use std::ops::Range;
+use std::sync::{Arc, OnceLock};
+
+type Finder = Arc<dyn Fn(&str) -> Vec<Range<usize>> + Send + Sync>;
+
+#[derive(Clone)]
+pub struct PiiDetector {
+    name: String,
+    replacement: String,
+    find: Finder,
+}
+
+impl std::fmt::Debug for PiiDetector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PiiDetector")
+            .field("name", &self.name)
+            .field("replacement", &self.replacement)
+            .finish()
+    }
+}
+
+impl PiiDetector {
+    pub fn regex(
+        name: impl Into<String>,
+        pattern: &str,
+        replacement: impl Into<String>,
+    ) -> Result<Self, TtsError> {
+        let pattern =
+            Regex::new(pattern).map_err(|e| TtsError::InvalidRedactionPattern(e.to_string()))?;
+        Ok(Self::custom(name, replacement, move |text| {
+            pattern.find_iter(text).map(|m| m.range()).collect()
+        }))
+    }
+
+    // `find` returns the byte ranges to redact
+    pub fn custom<F>(name: impl Into<String>, replacement: impl Into<String>, find: F) -> Self
+    where
+        F: Fn(&str) -> Vec<Range<usize>> + Send + Sync + 'static,
+    {
+        Self {
+            name: name.into(),
+            replacement: replacement.into(),
+            find: Arc::new(find),
+        }
+    }
+
+    pub fn email() -> Self {
+        Self::custom("email", "redacted email", |text| {
+            email_pattern().find_iter(text).map(|m| m.range()).collect()
+        })
+    }
+
+    // 13 to 19 digits, optionally grouped with spaces or dashes, passing the Luhn check
+    pub fn card_number() -> Self {
+        Self::custom("card_number", "redacted card number", |text| {
+            card_pattern()
+                .find_iter(text)
+                .filter(|m| luhn_valid(m.as_str()))
+                .map(|m| m.range())
+                .collect()
+        })
+    }
+
+    // 9 to 15 digits with optional country code, parentheses, and separators; shorter runs
+    // are more often years, amounts, or order numbers
+    pub fn phone_number() -> Self {
+        Self::custom("phone_number", "redacted phone number", |text| {
+            phone_pattern()
+                .find_iter(text)
+                .map(|m| m.range())
+                .filter(|range| phone_like(text, range))
+                .collect()
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn redact(&self, text: &str) -> String {
+        let mut ranges = (self.find)(text);
+        ranges.sort_by_key(|range| range.start);
+
+        let mut out = String::with_capacity(text.len());
+        let mut rest = 0;
+        for range in ranges {
+            // skip overlapping and out-of-bounds ranges from custom detectors
+            if range.start < rest
+                || range.end > text.len()
+                || !text.is_char_boundary(range.start)
+                || !text.is_char_boundary(range.end)
+            {
+                continue;
+            }
+            out.push_str(&text[rest..range.start]);
+            out.push_str(&self.replacement);
+            rest = range.end;
+        }
+        out.push_str(&text[rest..]);
+        out
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PiiRedactor {
+    detectors: Vec<PiiDetector>,
+}
+
+// card numbers run before phone numbers, which would otherwise match their digits
+impl Default for PiiRedactor {
+    fn default() -> Self {
+        Self::new()
+            .detector(PiiDetector::email())
+            .detector(PiiDetector::card_number())
+            .detector(PiiDetector::phone_number())
+    }
+}
+
+impl PiiRedactor {
+    // no detectors; add them with `detector`, or use `default()` for the built-in set
+    pub fn new() -> Self {
+        Self {
+            detectors: Vec::new(),
+        }
+    }
+
+    pub fn detector(mut self, detector: PiiDetector) -> Self {
+        self.detectors.push(detector);
+        self
+    }
+
+    // detectors run in order, each on the previous one's output
+    pub fn redact(&self, text: &str) -> String {
+        self.detectors
+            .iter()
+            .fold(text.to_string(), |text, detector| detector.redact(&text))
+    }
+}
+
+fn email_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}")
+            .expect("email pattern is valid")
+    })
+}
+
+fn card_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\b\d(?:[ -]?\d){12,18}\b").expect("card pattern is valid"))
+}
+
+// dots only separate digit groups when every group is dotted, as in 555.123.4567, so
+// decimals and lists of amounts don't run together into a number
+fn phone_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(concat!(
+            r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{1,4}\)[\s.-]?)?",
+            r"(?:\d{2,4}(?:\.\d{2,4}){2,4}\b|\d(?:[\s-]?\d){5,})",
+        ))
+        .expect("phone pattern is valid")
+    })
+}
+
+fn iso_date_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\d{4}-\d{2}-\d{2}").expect("date pattern is valid"))
+}
+
+fn phone_like(text: &str, range: &Range<usize>) -> bool {
+    let candidate = &text[range.clone()];
+    if !(9..=15).contains(&digit_count(candidate)) {
+        return false;
+    }
+    // the fraction of a decimal, like the digits after "3." in 3.14159265358
+    let after_decimal_point = text[..range.start]
+        .strip_suffix('.')
+        .is_some_and(|before| before.ends_with(|c: char| c.is_ascii_digit()));
+    // a list of years, like "2019 2020 2021"
+    let groups: Vec<&str> = candidate
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|group| !group.is_empty())
+        .collect();
+    let years = groups.len() > 1
+        && groups
+            .iter()
+            .all(|group| group.len() == 4 && (group.starts_with("19") || group.starts_with("20")));
+    !after_decimal_point && !years && !iso_date_pattern().is_match(candidate)
+}
+
+fn digit_count(text: &str) -> usize {
+    text.chars().filter(char::is_ascii_digit).count()
+}
+
+fn luhn_valid(number: &str) -> bool {
+    let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match (i % 2, d * 2) {
+            (0, _) => d,
+            (_, doubled) if doubled > 9 => doubled - 9,
+            (_, doubled) => doubled,
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emails() {
+        assert_eq!(
+            PiiDetector::email().redact("mail jane.doe+tts@example.co.uk now"),
+            "mail redacted email now"
+        );
+        assert_eq!(PiiDetector::email().redact("a @ b, x@y"), "a @ b, x@y");
+    }
+
+    #[test]
+    fn card_numbers_pass_the_luhn_check() {
+        assert_eq!(
+            PiiDetector::card_number().redact("card 4111 1111 1111 1111."),
+            "card redacted card number."
+        );
+        assert_eq!(
+            PiiDetector::card_number().redact("card 4111-1111-1111-1112"),
+            "card 4111-1111-1111-1112"
+        );
+        assert!(luhn_valid("79927398713"));
+        assert!(!luhn_valid("79927398710"));
+    }
+
+    #[test]
+    fn phone_numbers() {
+        for phone in [
+            "555-123-4567",
+            "555.123.4567",
+            "5551234567",
+            "+1 (555) 123-4567",
+            "+44 20 7946 0958",
+        ] {
+            assert_eq!(
+                PiiDetector::phone_number().redact(&format!("call {} today", phone)),
+                "call redacted phone number today",
+                "{}",
+                phone
+            );
+        }
+    }
+
+    #[test]
+    fn numbers_that_are_not_phone_numbers() {
+        for text in [
+            "order 12345678",
+            "pi is 3.14159265",
+            "pi is 3.14159265358",
+            "prices 12.50 13.75 14.99",
+            "in 2019 2020 2021",
+            "on 2024-03-05",
+            "at 2024-03-05 12:30",
+        ] {
+            assert_eq!(PiiDetector::phone_number().redact(text), text);
+        }
+    }
+
+    #[test]
+    fn default_redactor_checks_cards_before_phones() {
+        assert_eq!(
+            PiiRedactor::default().redact("4111 1111 1111 1111 or a@b.io or 555-123-4567"),
+            "redacted card number or redacted email or redacted phone number"
+        );
+    }
+
+    #[test]
+    fn custom_ranges_skip_overlaps_and_split_characters() {
+        let detector = PiiDetector::custom("test", "X", |_| vec![3..5, 2..4, 0..3, 5..10]);
+        assert_eq!(detector.redact("abcdef"), "XXf");
+        let detector = PiiDetector::custom("test", "X", |_| vec![1..2, 2..3]);
+        assert_eq!(detector.redact("é!"), "éX");
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        assert!(matches!(
+            PiiDetector::regex("broken", "(", "x"),
+            Err(TtsError::InvalidRedactionPattern(_))
+        ));
+        let detector = PiiDetector::regex("id", r"ID-\d+", "redacted id").unwrap();
+        assert_eq!(detector.redact("see ID-42."), "see redacted id.");
+    }
+}
@@ -0,0 +1,58 @@
+use crate::error::TtsError;
+use crate::hash::sha256_hex;
+use crate::types::{AudioFormat, SpeechRequest, SpeechRequestJson, TtsModel};
+use crate::util::write_file;
+use hyperware_process_lib::vfs;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReproBundle {
+    pub request: serde_json::Value,
+    pub voice_id: String,
+    pub output_format: AudioFormat,
+    pub query_params: Vec<(String, String)>,
+    pub model: TtsModel,
+    pub seed: u32,
+    pub audio_sha256: String,
+}
+
+impl ReproBundle {
+    pub(crate) fn capture(
+        request: &SpeechRequest,
+        seed: u32,
+        audio: &[u8],
+    ) -> Result<Self, TtsError> {
+        let json_request = SpeechRequestJson::from(request.clone());
+        let request_json = serde_json::to_value(&json_request)
+            .map_err(|e| TtsError::SerializationError(e.to_string()))?;
+
+        Ok(Self {
+            request: request_json,
+            voice_id: request.voice.as_voice_id().to_string(),
+            output_format: request.output_format.clone().unwrap_or_default(),
+            query_params: request.query_params.clone(),
+            model: request.model.clone(),
+            seed,
+            audio_sha256: sha256_hex(audio),
+        })
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), TtsError> {
+        let bytes = serde_json::to_vec_pretty(self)
+            .map_err(|e| TtsError::SerializationError(e.to_string()))?;
+        write_file(path, &bytes)
+    }
+
+    pub fn load(path: &str) -> Result<Self, TtsError> {
+        let file = vfs::open_file(path, false, None).map_err(|e| TtsError::Vfs(e.to_string()))?;
+        let bytes = file.read().map_err(|e| TtsError::Vfs(e.to_string()))?;
+        serde_json::from_slice(&bytes).map_err(|e| TtsError::DeserializationError(e.to_string()))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplayReport {
+    pub audio_data: Vec<u8>,
+    pub audio_sha256: String,
+    pub matches: bool,
+}
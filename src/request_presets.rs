@@ -0,0 +1,181 @@
+use crate::error::TtsError;
+use crate::types::{AudioFormat, SpeechRequest, TextNormalization, TtsModel, Voice, VoiceSettings};
+use hyperware_process_lib::vfs;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// the configurable part of a SpeechRequest, in a form that can live in a config file; context
+// and request ids, ID3 tags, hedging, and correlation ids are per call and left out
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RequestPreset {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    // the text, the preset's or the caller's, is SSML
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub ssml: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<TtsModel>,
+    // a built-in voice name ("Rachel") or a voice id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voice: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voice_alias: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voice_preset: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delivery: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voice_settings: Option<VoiceSettings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<AudioFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apply_text_normalization: Option<TextNormalization>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apply_language_text_normalization: Option<bool>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub with_timestamps: bool,
+    #[serde(skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra_fields: serde_json::Map<String, serde_json::Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub query_params: Vec<(String, String)>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+}
+
+impl RequestPreset {
+    pub fn from_request(request: &SpeechRequest) -> Self {
+        Self {
+            text: request.text.clone(),
+            ssml: request.ssml,
+            model: Some(request.model.clone()),
+            voice: Some(request.voice.name().to_string()),
+            voice_alias: request.voice_alias.clone(),
+            voice_preset: request.voice_preset.clone(),
+            delivery: request.delivery.clone(),
+            voice_settings: request.voice_settings.clone(),
+            output_format: request.output_format.clone(),
+            language_code: request.language_code.clone(),
+            seed: request.seed,
+            apply_text_normalization: request.apply_text_normalization.clone(),
+            apply_language_text_normalization: request.apply_language_text_normalization,
+            with_timestamps: request.with_timestamps,
+            extra_fields: request.extra_fields.clone(),
+            query_params: request.query_params.clone(),
+            tags: request.tags.clone(),
+        }
+    }
+
+    // fields the preset sets replace the request's; extra fields, query parameters, and tags
+    // are added to the request's own
+    pub fn apply(&self, mut request: SpeechRequest) -> SpeechRequest {
+        if !self.text.is_empty() {
+            request.text = self.text.clone();
+        }
+        if let Some(model) = &self.model {
+            request.model = model.clone();
+        }
+        if let Some(voice) = &self.voice {
+            request.voice = Voice::from_name(voice).unwrap_or_else(|| Voice::from_voice_id(voice));
+        }
+        if self.voice_alias.is_some() {
+            request.voice_alias = self.voice_alias.clone();
+        }
+        if self.voice_preset.is_some() {
+            request.voice_preset = self.voice_preset.clone();
+        }
+        if self.delivery.is_some() {
+            request.delivery = self.delivery.clone();
+        }
+        if self.voice_settings.is_some() {
+            request.voice_settings = self.voice_settings.clone();
+        }
+        if self.output_format.is_some() {
+            request.output_format = self.output_format.clone();
+        }
+        if self.language_code.is_some() {
+            request.language_code = self.language_code.clone();
+        }
+        if self.seed.is_some() {
+            request.seed = self.seed;
+        }
+        if self.apply_text_normalization.is_some() {
+            request.apply_text_normalization = self.apply_text_normalization.clone();
+        }
+        if self.apply_language_text_normalization.is_some() {
+            request.apply_language_text_normalization = self.apply_language_text_normalization;
+        }
+        request.ssml |= self.ssml;
+        request.with_timestamps |= self.with_timestamps;
+        request.extra_fields.extend(self.extra_fields.clone());
+        request
+            .query_params
+            .extend(self.query_params.iter().cloned());
+        request.tags.extend(self.tags.clone());
+        request
+    }
+}
+
+impl SpeechRequest {
+    pub fn from_json(json: &str) -> Result<Self, TtsError> {
+        let preset: RequestPreset = serde_json::from_str(json)
+            .map_err(|e| TtsError::DeserializationError(e.to_string()))?;
+        Ok(preset.apply(SpeechRequest::default()))
+    }
+
+    pub fn to_json(&self) -> Result<String, TtsError> {
+        serde_json::to_string_pretty(&RequestPreset::from_request(self))
+            .map_err(|e| TtsError::SerializationError(e.to_string()))
+    }
+}
+
+// named presets from a JSON object of the form `{"promo": {"voice": "Aria", ...}, ...}`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RequestPresets {
+    presets: BTreeMap<String, RequestPreset>,
+}
+
+impl RequestPresets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, TtsError> {
+        serde_json::from_str(json).map_err(|e| TtsError::DeserializationError(e.to_string()))
+    }
+
+    pub fn to_json(&self) -> Result<String, TtsError> {
+        serde_json::to_string_pretty(self).map_err(|e| TtsError::SerializationError(e.to_string()))
+    }
+
+    // reads the document from a VFS path, so it can be edited without rebuilding the process
+    pub fn load(path: &str) -> Result<Self, TtsError> {
+        let file = vfs::open_file(path, false, None).map_err(|e| TtsError::Vfs(e.to_string()))?;
+        let bytes = file.read().map_err(|e| TtsError::Vfs(e.to_string()))?;
+        serde_json::from_slice(&bytes).map_err(|e| TtsError::DeserializationError(e.to_string()))
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, preset: RequestPreset) {
+        self.presets.insert(name.into(), preset);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&RequestPreset> {
+        self.presets.get(name)
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.presets.keys().map(String::as_str).collect()
+    }
+
+    // a fresh request configured by the named preset
+    pub fn request(&self, name: &str) -> Result<SpeechRequest, TtsError> {
+        self.get(name)
+            .map(|preset| preset.apply(SpeechRequest::default()))
+            .ok_or_else(|| TtsError::UnknownRequestPreset(name.to_string()))
+    }
+}
@@ -0,0 +1,167 @@
+use crate::error::TtsError;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub enum Retryable {
+    TransientErrors,
+    Never,
+    Custom(Arc<dyn Fn(&TtsError) -> bool + Send + Sync>),
+}
+
+impl Retryable {
+    pub fn custom(predicate: impl Fn(&TtsError) -> bool + Send + Sync + 'static) -> Self {
+        Retryable::Custom(Arc::new(predicate))
+    }
+
+    pub fn is_retryable(&self, error: &TtsError) -> bool {
+        match self {
+            Retryable::TransientErrors => error.is_transient(),
+            Retryable::Never => false,
+            Retryable::Custom(predicate) => predicate(error),
+        }
+    }
+}
+
+impl fmt::Debug for Retryable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Retryable::TransientErrors => write!(f, "TransientErrors"),
+            Retryable::Never => write!(f, "Never"),
+            Retryable::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RetryClass {
+    // 429
+    RateLimited,
+    // 5xx
+    ServerError,
+    // HTTP client errors: timeouts, DNS failures, refused connections
+    Connectivity,
+}
+
+impl RetryClass {
+    pub fn of(error: &TtsError) -> Option<Self> {
+        match error {
+            TtsError::HttpClient(_) => Some(RetryClass::Connectivity),
+            TtsError::ApiError { status: 429, .. } => Some(RetryClass::RateLimited),
+            TtsError::ApiError { status, .. } if (500..600).contains(status) => {
+                Some(RetryClass::ServerError)
+            }
+            _ => None,
+        }
+    }
+}
+
+// attempts and backoff for one failure class, replacing the policy-wide values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClassRetry {
+    pub max_retries: u32,
+    pub backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl ClassRetry {
+    pub fn new(max_retries: u32) -> Self {
+        let defaults = RetryPolicy::default();
+        Self {
+            max_retries,
+            backoff_ms: defaults.backoff_ms,
+            max_backoff_ms: defaults.max_backoff_ms,
+        }
+    }
+
+    pub fn with_backoff(mut self, backoff_ms: u64, max_backoff_ms: u64) -> Self {
+        self.backoff_ms = backoff_ms;
+        self.max_backoff_ms = max_backoff_ms;
+        self
+    }
+
+    // exponential backoff: backoff_ms, 2 * backoff_ms, 4 * backoff_ms, ... capped at max_backoff_ms
+    pub fn delay_ms(&self, retry: u32) -> u64 {
+        self.backoff_ms
+            .saturating_mul(1u64 << retry.min(32))
+            .min(self.max_backoff_ms)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    pub retryable: Retryable,
+    pub classes: HashMap<RetryClass, ClassRetry>,
+    // wait at least as long as a 429's Retry-After header asks
+    pub respect_retry_after: bool,
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_backoff(mut self, backoff_ms: u64, max_backoff_ms: u64) -> Self {
+        self.backoff_ms = backoff_ms;
+        self.max_backoff_ms = max_backoff_ms;
+        self
+    }
+
+    pub fn with_retryable(mut self, retryable: Retryable) -> Self {
+        self.retryable = retryable;
+        self
+    }
+
+    pub fn with_class(mut self, class: RetryClass, retry: ClassRetry) -> Self {
+        self.classes.insert(class, retry);
+        self
+    }
+
+    pub fn with_retry_after(mut self, respect: bool) -> Self {
+        self.respect_retry_after = respect;
+        self
+    }
+
+    // the wait before retry number `retry` of this error's class, or None to give up
+    pub fn retry_delay_ms(&self, error: &TtsError, retry: u32) -> Option<u64> {
+        if !self.retryable.is_retryable(error) {
+            return None;
+        }
+        let settings = RetryClass::of(error)
+            .and_then(|class| self.classes.get(&class).copied())
+            .unwrap_or(ClassRetry {
+                max_retries: self.max_retries,
+                backoff_ms: self.backoff_ms,
+                max_backoff_ms: self.max_backoff_ms,
+            });
+        if retry >= settings.max_retries {
+            return None;
+        }
+
+        let delay = settings.delay_ms(retry);
+        match error.retry_after_ms() {
+            Some(retry_after) if self.respect_retry_after => Some(delay.max(retry_after)),
+            _ => Some(delay),
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            backoff_ms: 500,
+            max_backoff_ms: 30000,
+            retryable: Retryable::TransientErrors,
+            classes: HashMap::new(),
+            respect_retry_after: true,
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::audio::{silence, tone};
+use crate::error::TtsError;
+use crate::types::{Alignment, RawResponse, SpeechRequest};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use http::header::{HeaderValue, CONTENT_TYPE};
+use http::HeaderMap;
+use serde_json::json;
+
+// about 150 words a minute
+const DEFAULT_CHARS_PER_SECOND: f32 = 15.0;
+// long enough to pass the client's empty-audio check for one-character inputs
+const MIN_DURATION_MS: u64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SandboxAudio {
+    Silence,
+    Tone(f32),
+}
+
+// answers speech requests locally instead of calling the API, after the usual validation,
+// so integration environments exercise the whole pipeline without spending credits
+#[derive(Debug, Clone)]
+pub struct Sandbox {
+    audio: SandboxAudio,
+    chars_per_second: f32,
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self {
+            audio: SandboxAudio::Silence,
+            chars_per_second: DEFAULT_CHARS_PER_SECOND,
+        }
+    }
+}
+
+impl Sandbox {
+    pub fn silence() -> Self {
+        Self::default()
+    }
+
+    pub fn tone(frequency_hz: f32) -> Self {
+        Self {
+            audio: SandboxAudio::Tone(frequency_hz),
+            ..Self::default()
+        }
+    }
+
+    pub fn chars_per_second(mut self, chars_per_second: f32) -> Self {
+        self.chars_per_second = chars_per_second.max(1.0);
+        self
+    }
+
+    // the text's length at the configured speaking rate, scaled by the request's speed setting
+    pub fn estimated_duration_ms(&self, request: &SpeechRequest) -> u64 {
+        let speed = request
+            .voice_settings
+            .as_ref()
+            .and_then(|settings| settings.speed)
+            .unwrap_or(1.0);
+        let seconds = request.text.chars().count() as f32 / (self.chars_per_second * speed);
+        ((seconds * 1000.0) as u64).max(MIN_DURATION_MS)
+    }
+
+    pub(crate) fn respond(&self, request: &SpeechRequest) -> Result<RawResponse, TtsError> {
+        let format = request.output_format.clone().unwrap_or_default();
+        let duration_ms = self.estimated_duration_ms(request);
+        let audio = match self.audio {
+            SandboxAudio::Silence => silence(&format, duration_ms),
+            SandboxAudio::Tone(frequency_hz) => tone(&format, frequency_hz, duration_ms),
+        };
+
+        let mut headers = HeaderMap::new();
+        if !request.with_timestamps {
+            let content_type = if format.is_mp3() {
+                "audio/mpeg"
+            } else {
+                "application/octet-stream"
+            };
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+            return Ok(RawResponse {
+                status: 200,
+                headers,
+                body: audio,
+            });
+        }
+
+        let alignment = even_alignment(&request.text, duration_ms);
+        let body = serde_json::to_vec(&json!({
+            "audio_base64": BASE64.encode(&audio),
+            "alignment": alignment,
+            "normalized_alignment": alignment,
+        }))
+        .map_err(|e| TtsError::SerializationError(e.to_string()))?;
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        Ok(RawResponse {
+            status: 200,
+            headers,
+            body,
+        })
+    }
+}
+
+// characters spread evenly over the audio
+fn even_alignment(text: &str, duration_ms: u64) -> Alignment {
+    let characters: Vec<String> = text.chars().map(String::from).collect();
+    let step = duration_ms as f64 / 1000.0 / characters.len().max(1) as f64;
+    Alignment {
+        character_start_times_seconds: (0..characters.len()).map(|i| i as f64 * step).collect(),
+        character_end_times_seconds: (1..=characters.len()).map(|i| i as f64 * step).collect(),
+        characters,
+    }
+}
@@ -0,0 +1,141 @@
+use crate::client::{SpeechClient, MAX_STITCHED_REQUEST_IDS};
+use crate::error::TtsError;
+use crate::text::SentenceBuffer;
+use crate::types::{AudioFormat, SpeechRequest, SpeechResponse, TtsModel, Voice, VoiceSettings};
+use futures::stream::{self, Stream, StreamExt};
+use std::collections::VecDeque;
+use std::pin::Pin;
+
+const DEFAULT_HISTORY: usize = 3;
+
+pub struct SpeechSession<'a> {
+    client: &'a SpeechClient,
+    template: SpeechRequest,
+    history: usize,
+    texts: VecDeque<String>,
+    request_ids: VecDeque<String>,
+}
+
+impl<'a> SpeechSession<'a> {
+    pub(crate) fn new(client: &'a SpeechClient) -> Self {
+        Self {
+            client,
+            template: SpeechRequest::default(),
+            history: DEFAULT_HISTORY,
+            texts: VecDeque::new(),
+            request_ids: VecDeque::new(),
+        }
+    }
+
+    pub fn history(mut self, utterances: usize) -> Self {
+        self.history = utterances;
+        self
+    }
+
+    pub fn model(mut self, model: TtsModel) -> Self {
+        self.template.model = model;
+        self
+    }
+
+    pub fn voice(mut self, voice: Voice) -> Self {
+        self.template.voice = voice;
+        self
+    }
+
+    pub fn voice_settings(mut self, settings: VoiceSettings) -> Self {
+        self.template.voice_settings = Some(settings);
+        self
+    }
+
+    pub fn output_format(mut self, format: AudioFormat) -> Self {
+        self.template.output_format = Some(format);
+        self
+    }
+
+    pub fn language_code(mut self, code: impl Into<String>) -> Self {
+        self.template.language_code = Some(code.into());
+        self
+    }
+
+    // request ids take precedence; previous_text is only sent while none are known, since
+    // the API ignores it once previous_request_ids is set
+    pub async fn say(&mut self, text: impl Into<String>) -> Result<SpeechResponse, TtsError> {
+        let text = text.into();
+        let mut request = self.template.clone();
+        request.text = text.clone();
+
+        if !self.request_ids.is_empty() {
+            request.previous_request_ids = Some(self.request_ids.iter().cloned().collect());
+        } else if !self.texts.is_empty() {
+            let texts: Vec<&str> = self.texts.iter().map(String::as_str).collect();
+            request.previous_text = Some(texts.join(" "));
+        }
+
+        let response = self.client.send_speech_request(request).await?;
+        self.remember(text, response.request_id.clone());
+        Ok(response)
+    }
+
+    // speaks text as it streams in, e.g. LLM deltas, one buffered sentence group per request;
+    // dropping the returned stream stops synthesis after the current request
+    pub fn speak_stream<S>(
+        self,
+        text: S,
+        buffer: SentenceBuffer,
+    ) -> impl Stream<Item = Result<SpeechResponse, TtsError>> + 'a
+    where
+        S: Stream<Item = String> + 'a,
+    {
+        let state = SpeakState {
+            session: self,
+            text: Box::pin(text),
+            buffer,
+            pending: VecDeque::new(),
+            finished: false,
+        };
+        stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(text) = state.pending.pop_front() {
+                    let result = state.session.say(text).await;
+                    return Some((result, state));
+                }
+                if state.finished {
+                    return None;
+                }
+                match state.text.next().await {
+                    Some(delta) => state.pending.extend(state.buffer.push(&delta)),
+                    None => {
+                        state.finished = true;
+                        state.pending.extend(state.buffer.flush());
+                    }
+                }
+            }
+        })
+    }
+
+    pub fn reset(&mut self) {
+        self.texts.clear();
+        self.request_ids.clear();
+    }
+
+    fn remember(&mut self, text: String, request_id: Option<String>) {
+        self.texts.push_back(text);
+        if let Some(request_id) = request_id {
+            self.request_ids.push_back(request_id);
+        }
+        while self.texts.len() > self.history {
+            self.texts.pop_front();
+        }
+        while self.request_ids.len() > self.history.min(MAX_STITCHED_REQUEST_IDS) {
+            self.request_ids.pop_front();
+        }
+    }
+}
+
+struct SpeakState<'a, S> {
+    session: SpeechSession<'a>,
+    text: Pin<Box<S>>,
+    buffer: SentenceBuffer,
+    pending: VecDeque<String>,
+    finished: bool,
+}
@@ -0,0 +1,251 @@
+use crate::error::TtsError;
+use crate::hash::{constant_time_eq, from_hex, hmac_sha256, to_hex};
+use crate::util::now_secs;
+
+const DEFAULT_TTL_SECS: u64 = 24 * 60 * 60;
+
+// signs paths of served audio so a link works until it expires without the drive being
+// public. The signature is a hex HMAC-SHA256 of "<path>\n<expires>", where the path is
+// percent-encoded as it appears in the URL and `expires` is in unix seconds; both travel as
+// query parameters. Serving the path stays with the process,
+// which checks each request with `verify` before answering it.
+#[derive(Debug, Clone)]
+pub struct UrlSigner {
+    secret: String,
+    ttl_secs: u64,
+}
+
+impl UrlSigner {
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self {
+            secret: secret.into(),
+            ttl_secs: DEFAULT_TTL_SECS,
+        }
+    }
+
+    // how long links from `sign` stay valid
+    pub fn with_ttl(mut self, ttl_secs: u64) -> Self {
+        self.ttl_secs = ttl_secs;
+        self
+    }
+
+    // returns "<path>?expires=<unix seconds>&signature=<hex>"
+    pub fn sign(&self, path: &str) -> String {
+        self.sign_until(path, now_secs() + self.ttl_secs)
+    }
+
+    pub fn sign_until(&self, path: &str, expires_at_secs: u64) -> String {
+        let path = canonical_path(path);
+        format!(
+            "{}?expires={}&signature={}",
+            path,
+            expires_at_secs,
+            to_hex(&self.signature(&path, expires_at_secs))
+        )
+    }
+
+    // checks a request's path and query, as given by the HTTP server, and returns the signed
+    // path, percent-decoded; a scheme and host in front are ignored
+    pub fn verify(&self, url: &str) -> Result<String, TtsError> {
+        self.verify_at(url, now_secs())
+    }
+
+    pub fn verify_at(&self, url: &str, now_secs: u64) -> Result<String, TtsError> {
+        let url = match url.split_once("://") {
+            Some((_, rest)) => rest.find('/').map_or("/", |start| &rest[start..]),
+            None => url,
+        };
+        let (path, query) = url
+            .split_once('?')
+            .ok_or_else(|| TtsError::InvalidSignedUrl("missing query".to_string()))?;
+
+        let mut expires = None;
+        let mut signature = None;
+        for pair in query.split('&') {
+            match pair.split_once('=') {
+                Some(("expires", value)) => expires = Some(value),
+                Some(("signature", value)) => signature = Some(value),
+                _ => {}
+            }
+        }
+        let expires_at_secs: u64 = expires
+            .ok_or_else(|| TtsError::InvalidSignedUrl("missing expiry".to_string()))?
+            .parse()
+            .map_err(|_| TtsError::InvalidSignedUrl("malformed expiry".to_string()))?;
+        let signature = signature
+            .and_then(from_hex)
+            .ok_or_else(|| TtsError::InvalidSignedUrl("missing signature".to_string()))?;
+
+        // the signature is checked first, so an altered expiry reads as a mismatch
+        if !constant_time_eq(
+            &signature,
+            &self.signature(&canonical_path(path), expires_at_secs),
+        ) {
+            return Err(TtsError::InvalidSignedUrl("signature mismatch".to_string()));
+        }
+        if now_secs >= expires_at_secs {
+            return Err(TtsError::InvalidSignedUrl("expired".to_string()));
+        }
+        Ok(String::from_utf8_lossy(&percent_decode(path)).into_owned())
+    }
+
+    fn signature(&self, path: &str, expires_at_secs: u64) -> Vec<u8> {
+        let message = format!("{}\n{}", path, expires_at_secs);
+        hmac_sha256(self.secret.as_bytes(), message.as_bytes())
+    }
+}
+
+// the path as sent in a URL: bytes other than unreserved characters, "/", ":", and "@" are
+// percent-encoded. Decoding first means a raw path and its encoded form sign the same.
+fn canonical_path(path: &str) -> String {
+    percent_decode(path)
+        .into_iter()
+        .map(|b| match b {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'.'
+            | b'_'
+            | b'~'
+            | b'/'
+            | b':'
+            | b'@' => (b as char).to_string(),
+            b => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+// a "%" not followed by two hex digits is kept as it is
+fn percent_decode(path: &str) -> Vec<u8> {
+    let bytes = path.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match bytes[i] {
+            b'%' => bytes
+                .get(i + 1..i + 3)
+                .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                .and_then(|hex| from_hex(std::str::from_utf8(hex).ok()?)),
+            _ => None,
+        };
+        match escaped {
+            Some(byte) => {
+                out.extend(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATH: &str = "/tts:tts.os/audio/café.mp3";
+    const ENCODED_PATH: &str = "/tts:tts.os/audio/caf%C3%A9.mp3";
+    const EXPIRES: u64 = 1_700_000_000;
+
+    fn rejection(result: Result<String, TtsError>) -> String {
+        match result {
+            Err(TtsError::InvalidSignedUrl(reason)) => reason,
+            other => panic!("expected a signed URL error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn signature_known_answer() {
+        assert_eq!(
+            UrlSigner::new("secret").sign_until(PATH, EXPIRES),
+            format!(
+                "{}?expires={}&signature={}",
+                ENCODED_PATH,
+                EXPIRES,
+                "5dee885d7762a7bcea389ea44a2db3c7dbeff82854ba9eeca8877a10107998c5"
+            )
+        );
+    }
+
+    #[test]
+    fn verifies_until_expiry() {
+        let signer = UrlSigner::new("secret");
+        let url = signer.sign_until(PATH, EXPIRES);
+        assert_eq!(signer.verify_at(&url, EXPIRES - 1).unwrap(), PATH);
+        let full_url = format!("http://localhost:8080{}", url);
+        assert_eq!(signer.verify_at(&full_url, EXPIRES - 1).unwrap(), PATH);
+        assert_eq!(rejection(signer.verify_at(&url, EXPIRES)), "expired");
+    }
+
+    #[test]
+    fn raw_and_encoded_paths_sign_the_same() {
+        let signer = UrlSigner::new("secret");
+        assert_eq!(
+            signer.sign_until(PATH, EXPIRES),
+            signer.sign_until(ENCODED_PATH, EXPIRES)
+        );
+        // as an HTTP server hands it over, with lowercase escapes
+        let url = format!(
+            "/tts:tts.os/audio/caf%c3%a9.mp3?{}",
+            signer.sign_until(PATH, EXPIRES).split_once('?').unwrap().1
+        );
+        assert_eq!(signer.verify_at(&url, EXPIRES - 1).unwrap(), PATH);
+    }
+
+    #[test]
+    fn reserved_characters_in_file_names() {
+        let signer = UrlSigner::new("secret");
+        let url = signer.sign_until("/audio/what?&why 100%.mp3", EXPIRES);
+        assert!(url.starts_with("/audio/what%3F%26why%20100%25.mp3?expires="));
+        assert_eq!(
+            signer.verify_at(&url, EXPIRES - 1).unwrap(),
+            "/audio/what?&why 100%.mp3"
+        );
+    }
+
+    #[test]
+    fn rejects_altered_urls() {
+        let signer = UrlSigner::new("secret");
+        let url = signer.sign_until(PATH, EXPIRES);
+        let later = url.replace("expires=1700000000", "expires=1800000000");
+        assert_eq!(
+            rejection(signer.verify_at(&later, EXPIRES - 1)),
+            "signature mismatch"
+        );
+        let other_path = url.replace("caf%C3%A9", "cafe");
+        assert_eq!(
+            rejection(signer.verify_at(&other_path, EXPIRES - 1)),
+            "signature mismatch"
+        );
+        assert_eq!(
+            rejection(UrlSigner::new("other").verify_at(&url, EXPIRES - 1)),
+            "signature mismatch"
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_urls() {
+        let signer = UrlSigner::new("secret");
+        assert_eq!(rejection(signer.verify_at("", 0)), "missing query");
+        assert_eq!(
+            rejection(signer.verify_at("https://host", 0)),
+            "missing query"
+        );
+        assert_eq!(
+            rejection(signer.verify_at("/a?signature=00", 0)),
+            "missing expiry"
+        );
+        assert_eq!(
+            rejection(signer.verify_at("/a?expires=soon&signature=00", 0)),
+            "malformed expiry"
+        );
+        assert_eq!(
+            rejection(signer.verify_at("/a?expires=1&signature=xyz", 0)),
+            "missing signature"
+        );
+    }
+}
@@ -0,0 +1,363 @@
+use crate::html::decode_entities;
+use crate::types::{SpeechRequest, TextNormalization, TtsModel};
+use crate::verbalize::{cardinal_words, ordinal_words, verbalize};
+use regex::{Captures, Regex};
+use std::sync::OnceLock;
+
+// the API ignores break tags longer than this
+const MAX_BREAK_MS: u64 = 3000;
+// pauses at least this long become "[long pause]" for eleven_v3
+const LONG_PAUSE_MS: u64 = 1000;
+// breaks are held as a private-use placeholder while other elements are translated, so the
+// element patterns never see markup inside their content
+const BREAK_START: char = '\u{e000}';
+const BREAK_END: char = '\u{e001}';
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SsmlText {
+    pub text: String,
+    // a <say-as> type the translation can't spell out itself; the API's text normalization
+    // should handle it instead
+    pub needs_normalization: bool,
+}
+
+// translates <break>, <say-as>, <sub>, and <emphasis>; other elements are dropped and their
+// text kept. Breaks become break tags, or pause tags for eleven_v3, which has no break
+// support. Emphasis becomes capitals, or an audio tag for eleven_v3.
+pub fn translate_ssml(ssml: &str, model: &TtsModel, language: Option<&str>) -> SsmlText {
+    let v3 = *model == TtsModel::ElevenV3;
+    let mut needs_normalization = false;
+
+    let mut text = break_pattern()
+        .replace_all(ssml, |caps: &Captures| {
+            format!("{}{}{}", BREAK_START, break_ms(&caps[1]), BREAK_END)
+        })
+        .into_owned();
+
+    // innermost elements first, until nothing nested is left
+    loop {
+        let translated = sub_pattern()
+            .replace_all(&text, |caps: &Captures| {
+                attribute(&caps[1], "alias").unwrap_or_else(|| caps[2].to_string())
+            })
+            .into_owned();
+        let translated = say_as_pattern()
+            .replace_all(&translated, |caps: &Captures| {
+                let interpret_as = attribute(&caps[1], "interpret-as").unwrap_or_default();
+                say_as(&interpret_as, &decode_entities(&caps[2]), language).unwrap_or_else(|| {
+                    needs_normalization = true;
+                    caps[2].to_string()
+                })
+            })
+            .into_owned();
+        let translated = emphasis_pattern()
+            .replace_all(&translated, |caps: &Captures| {
+                let level = attribute(&caps[1], "level").unwrap_or_default();
+                emphasize(&level, &caps[2], v3)
+            })
+            .into_owned();
+        if translated == text {
+            break;
+        }
+        text = translated;
+    }
+
+    let text = tag_pattern().replace_all(&text, |caps: &Captures| {
+        let closing = caps[0].starts_with("</");
+        let name = caps.get(1).map_or("", |m| m.as_str()).to_ascii_lowercase();
+        // paragraph ends survive as blank lines, which sentence splitting respects
+        match (name.as_str(), closing) {
+            ("p", true) => "\n\n",
+            ("s", true) => " ",
+            _ => "",
+        }
+    });
+    let mut text = decode_entities(text.trim());
+
+    while let Some(start) = text.find(BREAK_START) {
+        let Some(end) = text[start..].find(BREAK_END).map(|end| start + end) else {
+            break;
+        };
+        let ms: u64 = text[start + BREAK_START.len_utf8()..end]
+            .parse()
+            .unwrap_or_default();
+        text.replace_range(start..end + BREAK_END.len_utf8(), &pause(ms, v3));
+    }
+
+    SsmlText {
+        text: text.trim().to_string(),
+        needs_normalization,
+    }
+}
+
+// translates a request whose text is SSML; a say-as the translation left as written turns on
+// text normalization unless the request chose a setting
+pub(crate) fn apply_ssml(mut request: SpeechRequest) -> SpeechRequest {
+    if !request.ssml {
+        return request;
+    }
+    let translated = translate_ssml(
+        &request.text,
+        &request.model,
+        request.language_code.as_deref(),
+    );
+    request.text = translated.text;
+    request.ssml = false;
+    if translated.needs_normalization && request.apply_text_normalization.is_none() {
+        request.apply_text_normalization = Some(TextNormalization::On);
+    }
+    request
+}
+
+fn say_as(interpret_as: &str, content: &str, language: Option<&str>) -> Option<String> {
+    let content = content.trim();
+    let british = language.is_some_and(|l| {
+        let l = l.to_ascii_lowercase();
+        l.ends_with("gb") || l.ends_with("au") || l.ends_with("ie") || l.ends_with("nz")
+    });
+    match interpret_as.to_ascii_lowercase().as_str() {
+        "characters" | "spell-out" | "letters" => Some(spaced(content, |c| !c.is_whitespace())),
+        "digits" | "telephone" => Some(spaced(content, |c| c.is_ascii_alphanumeric())),
+        "cardinal" | "number" => match content.replace(',', "").parse::<u64>() {
+            Ok(n) => Some(cardinal_words(n, british)),
+            Err(_) => Some(verbalize(content, language)).filter(|spoken| spoken != content),
+        },
+        "ordinal" => {
+            let digits = content.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+            digits
+                .replace(',', "")
+                .parse::<u64>()
+                .ok()
+                .map(|n| ordinal_words(n, british))
+        }
+        // ISO dates, currency amounts, and units verbalize locally; anything else is left
+        // to the API's normalization
+        _ => Some(verbalize(content, language)).filter(|spoken| spoken != content),
+    }
+}
+
+fn spaced(content: &str, keep: impl Fn(char) -> bool) -> String {
+    content
+        .chars()
+        .filter(|c| keep(*c))
+        .map(String::from)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn emphasize(level: &str, content: &str, v3: bool) -> String {
+    match (level.to_ascii_lowercase().as_str(), v3) {
+        ("none", _) => content.to_string(),
+        ("reduced", true) => format!("[softly] {}", content),
+        ("reduced", false) => content.to_string(),
+        (_, true) => format!("[emphasized] {}", content),
+        // capitals are the closest the other models have to stress
+        (_, false) => content.to_uppercase(),
+    }
+}
+
+fn pause(ms: u64, v3: bool) -> String {
+    if ms == 0 {
+        return " ".to_string();
+    }
+    if v3 {
+        let tag = if ms >= LONG_PAUSE_MS {
+            "[long pause]"
+        } else {
+            "[short pause]"
+        };
+        return format!(" {} ", tag);
+    }
+    let seconds = ms.min(MAX_BREAK_MS) as f64 / 1000.0;
+    format!(" <break time=\"{}s\" /> ", seconds)
+}
+
+// milliseconds for a break's `time` ("500ms", "1.5s") or `strength`; a bare break is medium
+fn break_ms(attributes: &str) -> u64 {
+    if let Some(time) = attribute(attributes, "time") {
+        let time = time.trim().to_ascii_lowercase();
+        let parsed = match time.strip_suffix("ms") {
+            Some(ms) => ms.trim().parse::<f64>().ok(),
+            None => time
+                .strip_suffix('s')
+                .and_then(|s| s.trim().parse::<f64>().ok())
+                .map(|s| s * 1000.0),
+        };
+        if let Some(ms) = parsed {
+            return ms.max(0.0) as u64;
+        }
+    }
+    match attribute(attributes, "strength").as_deref() {
+        Some("none") => 0,
+        Some("x-weak") => 200,
+        Some("weak") => 350,
+        Some("strong") => 750,
+        Some("x-strong") => 1200,
+        _ => 500,
+    }
+}
+
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    attribute_pattern()
+        .captures_iter(attributes)
+        .find(|caps| caps[1].eq_ignore_ascii_case(name))
+        .map(|caps| {
+            let value = caps
+                .get(2)
+                .or_else(|| caps.get(3))
+                .map_or("", |m| m.as_str());
+            decode_entities(value)
+        })
+}
+
+fn break_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?i)<break\b([^>]*?)/?>(?:\s*</break>)?").expect("break pattern is valid")
+    })
+}
+
+fn sub_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?i)<sub\b([^>]*)>([^<]*)</sub\s*>").expect("sub pattern is valid")
+    })
+}
+
+fn say_as_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?i)<say-as\b([^>]*)>([^<]*)</say-as\s*>").expect("say-as pattern is valid")
+    })
+}
+
+fn emphasis_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?i)<emphasis\b([^>]*)>([^<]*)</emphasis\s*>")
+            .expect("emphasis pattern is valid")
+    })
+}
+
+// any remaining tag, declaration, or comment
+fn tag_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"<!--[\s\S]*?-->|<[?!/]?\s*([A-Za-z][\w:-]*)?[^>]*>")
+            .expect("tag pattern is valid")
+    })
+}
+
+fn attribute_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r#"([\w:-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#)
+            .expect("attribute pattern is valid")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translate(ssml: &str) -> String {
+        translate_ssml(ssml, &TtsModel::ElevenMultilingualV2, None).text
+    }
+
+    #[test]
+    fn empty_input() {
+        let translated = translate_ssml("", &TtsModel::ElevenMultilingualV2, None);
+        assert_eq!(translated.text, "");
+        assert!(!translated.needs_normalization);
+    }
+
+    #[test]
+    fn breaks() {
+        assert_eq!(
+            translate("Hello<break time=\"1.5s\"/>world"),
+            "Hello <break time=\"1.5s\" /> world"
+        );
+        assert_eq!(
+            translate("Wait<break time=\"5000ms\"/>now"),
+            "Wait <break time=\"3s\" /> now"
+        );
+        assert_eq!(
+            translate_ssml(
+                "Hello<break strength=\"x-strong\"/>world",
+                &TtsModel::ElevenV3,
+                None
+            )
+            .text,
+            "Hello [long pause] world"
+        );
+    }
+
+    #[test]
+    fn say_as() {
+        assert_eq!(
+            translate("<say-as interpret-as=\"characters\">AB1</say-as>"),
+            "A B 1"
+        );
+        assert_eq!(
+            translate("<say-as interpret-as=\"telephone\">555-1234</say-as>"),
+            "5 5 5 1 2 3 4"
+        );
+        assert_eq!(
+            translate("the <say-as interpret-as=\"ordinal\">3rd</say-as>"),
+            "the third"
+        );
+    }
+
+    #[test]
+    fn say_as_left_to_the_api_needs_normalization() {
+        let translated = translate_ssml(
+            "<say-as interpret-as=\"address\">Main St</say-as>",
+            &TtsModel::ElevenMultilingualV2,
+            None,
+        );
+        assert_eq!(translated.text, "Main St");
+        assert!(translated.needs_normalization);
+    }
+
+    #[test]
+    fn nested_sub_and_emphasis() {
+        let ssml = "<emphasis level=\"strong\"><sub alias=\"World Wide Web\">WWW</sub></emphasis>";
+        assert_eq!(translate(ssml), "WORLD WIDE WEB");
+        assert_eq!(
+            translate_ssml(ssml, &TtsModel::ElevenV3, None).text,
+            "[emphasized] World Wide Web"
+        );
+    }
+
+    #[test]
+    fn unknown_elements_and_malformed_markup() {
+        assert_eq!(
+            translate("<speak><p>One.</p><p>Two.</p></speak>"),
+            "One.\n\nTwo."
+        );
+        assert_eq!(
+            translate("<sub alias=\"café\">cafe</sub> naïve"),
+            "café naïve"
+        );
+        // a tag that is never closed is kept as text
+        assert_eq!(
+            translate("Fish &amp; chips <unclosed"),
+            "Fish & chips <unclosed"
+        );
+    }
+
+    #[test]
+    fn apply_ssml_turns_on_normalization_for_untranslated_say_as() {
+        let request = apply_ssml(SpeechRequest {
+            text: "<say-as interpret-as=\"address\">Main St</say-as>".to_string(),
+            ssml: true,
+            ..SpeechRequest::default()
+        });
+        assert_eq!(request.text, "Main St");
+        assert!(!request.ssml);
+        assert!(matches!(
+            request.apply_text_normalization,
+            Some(TextNormalization::On)
+        ));
+    }
+}
@@ -0,0 +1,213 @@
+use crate::client::SpeechClient;
+use crate::error::TtsError;
+use crate::hash::sha256_hex;
+use crate::types::{AudioFormat, SpeechRequest, TtsModel, Voice, VoiceSettings};
+use crate::util::{capped_concurrency, write_file};
+use futures::stream::{self, StreamExt};
+use hyperware_process_lib::vfs;
+use serde::{Deserialize, Serialize};
+
+const INDEX_FILE: &str = "index.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepIndex {
+    pub text: String,
+    pub voice: Voice,
+    pub model: TtsModel,
+    pub format: AudioFormat,
+    pub entries: Vec<SweepEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepEntry {
+    // e.g. "stability-0.30_similarity-0.75_style-0.00"; also the file's name
+    pub label: String,
+    pub voice_settings: VoiceSettings,
+    pub path: Option<String>,
+    pub duration_ms: u64,
+    pub sha256: Option<String>,
+    // a failed point doesn't stop the sweep; its error is recorded instead of audio
+    pub error: Option<String>,
+}
+
+// synthesizes one phrase at every combination of the given stability, similarity boost, and
+// style values, writing `{directory}/{label}.<ext>` for each and an index.json describing
+// them all. An axis with no values keeps the base settings' value.
+pub struct SweepBuilder<'a> {
+    client: &'a SpeechClient,
+    directory: String,
+    template: SpeechRequest,
+    stability: Vec<f32>,
+    similarity_boost: Vec<f32>,
+    style: Vec<f32>,
+    concurrency: usize,
+}
+
+impl<'a> SweepBuilder<'a> {
+    pub(crate) fn new(client: &'a SpeechClient, directory: String) -> Self {
+        Self {
+            client,
+            directory: directory.trim_end_matches('/').to_string(),
+            template: SpeechRequest::default(),
+            stability: Vec::new(),
+            similarity_boost: Vec::new(),
+            style: Vec::new(),
+            concurrency: 1,
+        }
+    }
+
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.template.text = text.into();
+        self
+    }
+
+    pub fn model(mut self, model: TtsModel) -> Self {
+        self.template.model = model;
+        self
+    }
+
+    pub fn voice(mut self, voice: Voice) -> Self {
+        self.template.voice = voice;
+        self
+    }
+
+    // the settings every point starts from, e.g. for speed or speaker boost
+    pub fn voice_settings(mut self, settings: VoiceSettings) -> Self {
+        self.template.voice_settings = Some(settings);
+        self
+    }
+
+    pub fn output_format(mut self, format: AudioFormat) -> Self {
+        self.template.output_format = Some(format);
+        self
+    }
+
+    pub fn language_code(mut self, code: impl Into<String>) -> Self {
+        self.template.language_code = Some(code.into());
+        self
+    }
+
+    // a fixed seed keeps differences between points down to the settings
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.template.seed = Some(seed);
+        self
+    }
+
+    pub fn stability(mut self, values: impl IntoIterator<Item = f32>) -> Self {
+        self.stability = values.into_iter().collect();
+        self
+    }
+
+    pub fn similarity_boost(mut self, values: impl IntoIterator<Item = f32>) -> Self {
+        self.similarity_boost = values.into_iter().collect();
+        self
+    }
+
+    pub fn style(mut self, values: impl IntoIterator<Item = f32>) -> Self {
+        self.style = values.into_iter().collect();
+        self
+    }
+
+    pub fn concurrency(mut self, requests: usize) -> Self {
+        self.concurrency = requests.max(1);
+        self
+    }
+
+    // every combination, in stability, then similarity boost, then style order
+    pub fn grid(&self) -> Vec<VoiceSettings> {
+        let base = self.template.voice_settings.clone().unwrap_or_default();
+        let axis = |values: &[f32], default: Option<f32>| -> Vec<Option<f32>> {
+            if values.is_empty() {
+                vec![default]
+            } else {
+                values.iter().copied().map(Some).collect()
+            }
+        };
+
+        let mut grid = Vec::new();
+        for stability in axis(&self.stability, base.stability) {
+            for similarity_boost in axis(&self.similarity_boost, base.similarity_boost) {
+                for style in axis(&self.style, base.style) {
+                    grid.push(VoiceSettings {
+                        stability,
+                        similarity_boost,
+                        style,
+                        ..base.clone()
+                    });
+                }
+            }
+        }
+        grid
+    }
+
+    pub async fn execute(self) -> Result<SweepIndex, TtsError> {
+        if self.template.text.trim().is_empty() {
+            return Err(TtsError::MissingInput);
+        }
+        let format = self.template.output_format.clone().unwrap_or_default();
+        vfs::open_dir(&self.directory, true, None).map_err(|e| TtsError::Vfs(e.to_string()))?;
+
+        let concurrency = capped_concurrency(self.concurrency, self.client.account_capabilities());
+        let entries = stream::iter(self.grid())
+            .map(|settings| self.render_point(settings, &format))
+            .buffered(concurrency)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let index = SweepIndex {
+            text: self.template.text.clone(),
+            voice: self.template.voice.clone(),
+            model: self.template.model.clone(),
+            format,
+            entries,
+        };
+        let index_json = serde_json::to_vec_pretty(&index)
+            .map_err(|e| TtsError::SerializationError(e.to_string()))?;
+        write_file(&format!("{}/{}", self.directory, INDEX_FILE), &index_json)?;
+        Ok(index)
+    }
+
+    // only failing to write the audio is fatal; a failed request is recorded in the entry
+    async fn render_point(
+        &self,
+        settings: VoiceSettings,
+        format: &AudioFormat,
+    ) -> Result<SweepEntry, TtsError> {
+        let label = label(&settings);
+        let mut request = self.template.clone();
+        request.voice_settings = Some(settings.clone());
+
+        let mut entry = SweepEntry {
+            label,
+            voice_settings: settings,
+            path: None,
+            duration_ms: 0,
+            sha256: None,
+            error: None,
+        };
+        match self.client.send_speech_request(request).await {
+            Ok(response) => {
+                let path = format!("{}/{}.{}", self.directory, entry.label, format.extension());
+                write_file(&path, &response.audio_data)?;
+                entry.duration_ms = format.estimated_duration_ms(response.audio_data.len());
+                entry.sha256 = Some(sha256_hex(&response.audio_data));
+                entry.path = Some(path);
+            }
+            Err(e) => entry.error = Some(e.to_string()),
+        }
+        Ok(entry)
+    }
+}
+
+// axes left at the API default are marked "default"
+fn label(settings: &VoiceSettings) -> String {
+    let value = |value: Option<f32>| value.map_or("default".to_string(), |v| format!("{:.2}", v));
+    format!(
+        "stability-{}_similarity-{}_style-{}",
+        value(settings.stability),
+        value(settings.similarity_boost),
+        value(settings.style)
+    )
+}
@@ -0,0 +1,156 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde_json::{json, Value};
+
+// 20 ms of 8 kHz µ-law, one byte per sample
+pub const FRAME_SAMPLES: usize = 160;
+pub const FRAME_DURATION_MS: u64 = 20;
+// µ-law encodes zero amplitude as 0xff
+const ULAW_SILENCE: u8 = 0xff;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UlawFrame {
+    // RTP-style counters: the sequence number wraps at u16, the timestamp counts samples
+    pub sequence: u16,
+    pub timestamp: u32,
+    pub payload: Vec<u8>,
+    // true when the payload was filled (entirely or in part) with silence
+    pub padded: bool,
+}
+
+// slices `ulaw_8000` audio, in chunks of any size, into 20 ms frames
+#[derive(Debug, Clone, Default)]
+pub struct UlawFramer {
+    sequence: u16,
+    timestamp: u32,
+    pending: Vec<u8>,
+}
+
+impl UlawFramer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // RTP senders start both counters at random values
+    pub fn with_initial(mut self, sequence: u16, timestamp: u32) -> Self {
+        self.sequence = sequence;
+        self.timestamp = timestamp;
+        self
+    }
+
+    // returns every complete frame; the remainder waits for the next chunk or `flush`
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<UlawFrame> {
+        self.pending.extend_from_slice(chunk);
+        let complete = self.pending.len() / FRAME_SAMPLES * FRAME_SAMPLES;
+        let ready: Vec<u8> = self.pending.drain(..complete).collect();
+        ready
+            .chunks_exact(FRAME_SAMPLES)
+            .map(|payload| self.next_frame(payload.to_vec(), false))
+            .collect()
+    }
+
+    // pads any buffered remainder with silence to a full frame
+    pub fn flush(&mut self) -> Option<UlawFrame> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let mut payload = std::mem::take(&mut self.pending);
+        payload.resize(FRAME_SAMPLES, ULAW_SILENCE);
+        Some(self.next_frame(payload, true))
+    }
+
+    // for underruns: keeps the sender's clock running when no audio has arrived in time
+    pub fn silence_frame(&mut self) -> UlawFrame {
+        self.next_frame(vec![ULAW_SILENCE; FRAME_SAMPLES], true)
+    }
+
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn next_frame(&mut self, payload: Vec<u8>, padded: bool) -> UlawFrame {
+        let frame = UlawFrame {
+            sequence: self.sequence,
+            timestamp: self.timestamp,
+            payload,
+            padded,
+        };
+        self.sequence = self.sequence.wrapping_add(1);
+        self.timestamp = self.timestamp.wrapping_add(FRAME_SAMPLES as u32);
+        frame
+    }
+}
+
+// frames a complete `ulaw_8000` response, padding the last frame with silence
+pub fn frame_ulaw(audio: &[u8]) -> Vec<UlawFrame> {
+    let mut framer = UlawFramer::new();
+    let mut frames = framer.push(audio);
+    frames.extend(framer.flush());
+    frames
+}
+
+// builds the JSON text messages telephony media-stream websockets expect, from a template in
+// which the strings "$payload" (base64 audio), "$sequence", "$timestamp", and "$chunk"
+// (a per-formatter message counter) are substituted wherever they appear
+#[derive(Debug, Clone)]
+pub struct MediaStreamFormatter {
+    template: Value,
+    chunk: u64,
+}
+
+impl MediaStreamFormatter {
+    pub fn new(template: Value) -> Self {
+        Self { template, chunk: 0 }
+    }
+
+    // {"event":"media","streamSid":..,"media":{"payload":..}}
+    pub fn twilio(stream_sid: impl Into<String>) -> Self {
+        Self::new(json!({
+            "event": "media",
+            "streamSid": stream_sid.into(),
+            "media": { "payload": "$payload" },
+        }))
+    }
+
+    pub fn format_chunk(&mut self, audio: &[u8]) -> String {
+        self.render(audio, None, None)
+    }
+
+    pub fn format_frame(&mut self, frame: &UlawFrame) -> String {
+        self.render(&frame.payload, Some(frame.sequence), Some(frame.timestamp))
+    }
+
+    fn render(&mut self, audio: &[u8], sequence: Option<u16>, timestamp: Option<u32>) -> String {
+        self.chunk += 1;
+        let substitutions = [
+            ("$payload", Value::from(BASE64.encode(audio))),
+            ("$sequence", Value::from(sequence)),
+            ("$timestamp", Value::from(timestamp)),
+            ("$chunk", Value::from(self.chunk)),
+        ];
+        substitute(&self.template, &substitutions).to_string()
+    }
+}
+
+fn substitute(value: &Value, substitutions: &[(&str, Value)]) -> Value {
+    match value {
+        Value::String(s) => substitutions
+            .iter()
+            .find(|(placeholder, _)| placeholder == s)
+            .map(|(_, replacement)| replacement.clone())
+            .unwrap_or_else(|| value.clone()),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| substitute(item, substitutions))
+                .collect(),
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, field)| (key.clone(), substitute(field, substitutions)))
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
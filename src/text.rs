@@ -0,0 +1,217 @@
+const SENTENCE_TERMINATORS: [char; 4] = ['.', '!', '?', '…'];
+
+pub fn split_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|(_, next)| *next);
+        let is_boundary = match next {
+            Some(next) => {
+                (SENTENCE_TERMINATORS.contains(&c) && next.is_whitespace())
+                    || (c == '\n' && next == '\n')
+            }
+            None => true,
+        };
+
+        if is_boundary {
+            let end = i + c.len_utf8();
+            let sentence = text[start..end].trim();
+            if !sentence.is_empty() {
+                sentences.push(sentence);
+            }
+            start = end;
+        }
+    }
+
+    sentences
+}
+
+pub fn chunk_text(text: &str, max_len: usize) -> Vec<String> {
+    let max_len = max_len.max(1);
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for sentence in split_sentences(text) {
+        for piece in split_oversized(sentence, max_len) {
+            let needed = if current.is_empty() {
+                piece.len()
+            } else {
+                current.len() + 1 + piece.len()
+            };
+            if needed > max_len && !current.is_empty() {
+                chunks.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(piece);
+        }
+    }
+
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+
+    chunks
+}
+
+fn split_oversized(sentence: &str, max_len: usize) -> Vec<&str> {
+    if sentence.len() <= max_len {
+        return vec![sentence];
+    }
+
+    let mut pieces = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut end = 0;
+
+    for (i, word) in sentence
+        .split_whitespace()
+        .map(|w| (offset_of(sentence, w), w))
+    {
+        if word.len() > max_len {
+            if let Some(s) = start.take() {
+                pieces.push(&sentence[s..end]);
+            }
+            pieces.extend(split_at_char_boundaries(word, max_len));
+            continue;
+        }
+
+        match start {
+            Some(s) if i + word.len() - s > max_len => {
+                pieces.push(&sentence[s..end]);
+                start = Some(i);
+            }
+            Some(_) => {}
+            None => start = Some(i),
+        }
+        end = i + word.len();
+    }
+
+    if let Some(s) = start {
+        pieces.push(&sentence[s..end]);
+    }
+
+    pieces
+}
+
+fn split_at_char_boundaries(word: &str, max_len: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+
+    while start < word.len() {
+        let mut end = (start + max_len).min(word.len());
+        while !word.is_char_boundary(end) {
+            end -= 1;
+        }
+        if end == start {
+            end = start + word[start..].chars().next().map_or(1, char::len_utf8);
+        }
+        pieces.push(&word[start..end]);
+        start = end;
+    }
+
+    pieces
+}
+
+fn offset_of(haystack: &str, needle: &str) -> usize {
+    needle.as_ptr() as usize - haystack.as_ptr() as usize
+}
+
+const CLAUSE_BREAKS: [char; 4] = [',', ';', ':', '—'];
+const DEFAULT_MIN_BUFFERED: usize = 20;
+const DEFAULT_MAX_BUFFERED: usize = 300;
+
+// collects streamed text, e.g. LLM token deltas, and releases it at sentence boundaries once
+// at least `min_len` bytes are waiting; text that runs past `max_len` without a sentence end
+// is released at the last clause break or space instead
+#[derive(Debug, Clone)]
+pub struct SentenceBuffer {
+    buffer: String,
+    min_len: usize,
+    max_len: usize,
+}
+
+impl Default for SentenceBuffer {
+    fn default() -> Self {
+        Self {
+            buffer: String::new(),
+            min_len: DEFAULT_MIN_BUFFERED,
+            max_len: DEFAULT_MAX_BUFFERED,
+        }
+    }
+}
+
+impl SentenceBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn min_len(mut self, bytes: usize) -> Self {
+        self.min_len = bytes;
+        self
+    }
+
+    pub fn max_len(mut self, bytes: usize) -> Self {
+        self.max_len = bytes.max(1);
+        self
+    }
+
+    pub fn push(&mut self, text: &str) -> Vec<String> {
+        self.buffer.push_str(text);
+        let mut released = Vec::new();
+        loop {
+            let sentence_end = last_sentence_end(&self.buffer)
+                .filter(|end| self.buffer[..*end].trim().len() >= self.min_len);
+            let end = match sentence_end {
+                Some(end) => end,
+                None if self.buffer.len() > self.max_len => clause_end(&self.buffer, self.max_len),
+                None => break,
+            };
+            released.extend(self.take(end));
+        }
+        released
+    }
+
+    // whatever is left once the source ends
+    pub fn flush(&mut self) -> Option<String> {
+        self.take(self.buffer.len())
+    }
+
+    fn take(&mut self, end: usize) -> Option<String> {
+        let rest = self.buffer.split_off(end);
+        let taken = std::mem::replace(&mut self.buffer, rest);
+        Some(taken.trim().to_string()).filter(|text| !text.is_empty())
+    }
+}
+
+// just past the last terminator known to end a sentence, i.e. one already followed by
+// whitespace
+fn last_sentence_end(text: &str) -> Option<usize> {
+    let mut end = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|(_, next)| *next);
+        let terminated = SENTENCE_TERMINATORS.contains(&c) && next.is_some_and(char::is_whitespace);
+        if terminated || (c == '\n' && next == Some('\n')) {
+            end = Some(i + c.len_utf8());
+        }
+    }
+    end
+}
+
+fn clause_end(text: &str, max_len: usize) -> usize {
+    let mut limit = max_len.min(text.len());
+    while !text.is_char_boundary(limit) {
+        limit -= 1;
+    }
+    if limit == 0 {
+        return text.chars().next().map_or(text.len(), char::len_utf8);
+    }
+    let head = &text[..limit];
+    head.rfind(CLAUSE_BREAKS)
+        .map(|i| i + head[i..].chars().next().map_or(1, char::len_utf8))
+        .or_else(|| head.rfind(char::is_whitespace).filter(|i| *i > 0))
+        .unwrap_or(limit)
+}
@@ -1,6 +1,9 @@
+use crate::id3::Id3Tags;
+use crate::repro::ReproBundle;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TtsModel {
     #[serde(rename = "eleven_v3")]
     ElevenV3,
@@ -29,7 +32,20 @@ impl Default for TtsModel {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+const NAMED_VOICES: [Voice; 10] = [
+    Voice::Rachel,
+    Voice::Drew,
+    Voice::Clyde,
+    Voice::Paul,
+    Voice::Aria,
+    Voice::Domi,
+    Voice::Dave,
+    Voice::Roger,
+    Voice::Fin,
+    Voice::Sarah,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Voice {
     Rachel,
     Drew,
@@ -41,6 +57,7 @@ pub enum Voice {
     Roger,
     Fin,
     Sarah,
+    Custom(String),
 }
 
 impl Voice {
@@ -57,6 +74,37 @@ impl Voice {
             Voice::Roger => "CwhRBWXzGAHq8TQ4Fs17",
             Voice::Fin => "D38z5RcWu1voky8WS1ja",
             Voice::Sarah => "EXAVITQu4vr4xnSDxMaL",
+            Voice::Custom(id) => id,
+        }
+    }
+
+    pub fn from_voice_id(voice_id: &str) -> Self {
+        NAMED_VOICES
+            .into_iter()
+            .find(|voice| voice.as_voice_id() == voice_id)
+            .unwrap_or_else(|| Voice::Custom(voice_id.to_string()))
+    }
+
+    // the named voice for `name`, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        NAMED_VOICES
+            .into_iter()
+            .find(|voice| voice.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Voice::Rachel => "Rachel",
+            Voice::Drew => "Drew",
+            Voice::Clyde => "Clyde",
+            Voice::Paul => "Paul",
+            Voice::Aria => "Aria",
+            Voice::Domi => "Domi",
+            Voice::Dave => "Dave",
+            Voice::Roger => "Roger",
+            Voice::Fin => "Fin",
+            Voice::Sarah => "Sarah",
+            Voice::Custom(id) => id,
         }
     }
 }
@@ -67,7 +115,7 @@ impl Default for Voice {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AudioFormat {
     #[serde(rename = "mp3_22050_32")]
     Mp3_22050_32,
@@ -109,6 +157,46 @@ impl AudioFormat {
             AudioFormat::Ulaw8000 => "ulaw_8000",
         }
     }
+
+    pub fn is_mp3(&self) -> bool {
+        matches!(
+            self,
+            AudioFormat::Mp3_22050_32
+                | AudioFormat::Mp3_44100_32
+                | AudioFormat::Mp3_44100_64
+                | AudioFormat::Mp3_44100_96
+                | AudioFormat::Mp3_44100_128
+                | AudioFormat::Mp3_44100_192
+        )
+    }
+
+    pub fn extension(&self) -> &str {
+        match self {
+            AudioFormat::Pcm16000
+            | AudioFormat::Pcm22050
+            | AudioFormat::Pcm24000
+            | AudioFormat::Pcm44100 => "pcm",
+            AudioFormat::Ulaw8000 => "ulaw",
+            _ => "mp3",
+        }
+    }
+
+    // mp3 outputs are constant bitrate, pcm is 16-bit mono
+    pub fn estimated_duration_ms(&self, len: usize) -> u64 {
+        let len = len as u64;
+        match self {
+            AudioFormat::Mp3_22050_32 | AudioFormat::Mp3_44100_32 => len * 8 / 32,
+            AudioFormat::Mp3_44100_64 => len * 8 / 64,
+            AudioFormat::Mp3_44100_96 => len * 8 / 96,
+            AudioFormat::Mp3_44100_128 => len * 8 / 128,
+            AudioFormat::Mp3_44100_192 => len * 8 / 192,
+            AudioFormat::Pcm16000 => len * 1000 / (2 * 16000),
+            AudioFormat::Pcm22050 => len * 1000 / (2 * 22050),
+            AudioFormat::Pcm24000 => len * 1000 / (2 * 24000),
+            AudioFormat::Pcm44100 => len * 1000 / (2 * 44100),
+            AudioFormat::Ulaw8000 => len * 1000 / 8000,
+        }
+    }
 }
 
 impl Default for AudioFormat {
@@ -127,6 +215,8 @@ pub struct VoiceSettings {
     pub style: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub use_speaker_boost: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f32>,
 }
 
 impl Default for VoiceSettings {
@@ -136,6 +226,7 @@ impl Default for VoiceSettings {
             similarity_boost: None,
             style: None,
             use_speaker_boost: None,
+            speed: None,
         }
     }
 }
@@ -154,6 +245,20 @@ impl Default for TextNormalization {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputLengthPolicy {
+    #[default]
+    Reject,
+    Truncate,
+    Chunk,
+}
+
+#[derive(Debug, Clone)]
+pub struct HedgeConfig {
+    pub model: TtsModel,
+    pub delay_ms: u64,
+}
+
 #[derive(Debug, Clone)]
 pub struct SpeechRequest {
     pub text: String,
@@ -169,6 +274,20 @@ pub struct SpeechRequest {
     pub next_request_ids: Option<Vec<String>>,
     pub apply_text_normalization: Option<TextNormalization>,
     pub apply_language_text_normalization: Option<bool>,
+    pub id3_tags: Option<Id3Tags>,
+    pub hedge: Option<HedgeConfig>,
+    pub preset: Option<String>,
+    pub voice_alias: Option<String>,
+    pub voice_preset: Option<String>,
+    pub delivery: Option<String>,
+    pub with_timestamps: bool,
+    // the text is SSML, translated before sending
+    pub ssml: bool,
+    pub extra_fields: serde_json::Map<String, serde_json::Value>,
+    pub query_params: Vec<(String, String)>,
+    pub tags: BTreeMap<String, String>,
+    // identifies the request in lifecycle events
+    pub correlation_id: Option<String>,
 }
 
 impl Default for SpeechRequest {
@@ -187,6 +306,18 @@ impl Default for SpeechRequest {
             next_request_ids: None,
             apply_text_normalization: None,
             apply_language_text_normalization: None,
+            id3_tags: None,
+            hedge: None,
+            preset: None,
+            voice_alias: None,
+            voice_preset: None,
+            delivery: None,
+            with_timestamps: false,
+            ssml: false,
+            extra_fields: serde_json::Map::new(),
+            query_params: Vec::new(),
+            tags: BTreeMap::new(),
+            correlation_id: None,
         }
     }
 }
@@ -213,6 +344,8 @@ pub struct SpeechRequestJson {
     pub apply_text_normalization: Option<TextNormalization>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub apply_language_text_normalization: Option<bool>,
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra_fields: serde_json::Map<String, serde_json::Value>,
 }
 
 impl From<SpeechRequest> for SpeechRequestJson {
@@ -229,6 +362,7 @@ impl From<SpeechRequest> for SpeechRequestJson {
             next_request_ids: req.next_request_ids,
             apply_text_normalization: req.apply_text_normalization,
             apply_language_text_normalization: req.apply_language_text_normalization,
+            extra_fields: req.extra_fields,
         }
     }
 }
@@ -236,7 +370,37 @@ impl From<SpeechRequest> for SpeechRequestJson {
 #[derive(Debug, Clone)]
 pub struct SpeechResponse {
     pub audio_data: Vec<u8>,
+    pub audio_sha256: String,
     pub format: AudioFormat,
+    pub model: TtsModel,
+    pub voice: Voice,
+    pub request_id: Option<String>,
+    pub repro_bundle: Option<ReproBundle>,
+    pub tags: BTreeMap<String, String>,
+    pub warnings: Vec<String>,
+    pub alignment: Option<Alignment>,
+    pub normalized_alignment: Option<Alignment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alignment {
+    pub characters: Vec<String>,
+    pub character_start_times_seconds: Vec<f64>,
+    pub character_end_times_seconds: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TimestampsResponse {
+    pub audio_base64: String,
+    pub alignment: Option<Alignment>,
+    pub normalized_alignment: Option<Alignment>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RawResponse {
+    pub status: u16,
+    pub headers: http::HeaderMap,
+    pub body: Vec<u8>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -251,3 +415,14 @@ pub struct ApiErrorDetail {
     pub error_type: Option<String>,
     pub code: Option<String>,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct DetailErrorResponse {
+    pub detail: DetailError,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DetailError {
+    pub status: Option<String>,
+    pub message: String,
+}
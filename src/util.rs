@@ -0,0 +1,64 @@
+use crate::account::AccountCapabilities;
+use crate::error::TtsError;
+use hyperware_process_lib::kv::Kv;
+use hyperware_process_lib::vfs;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// a file that can't be opened, or is empty, reads as absent
+pub(crate) fn read_file(path: &str) -> Result<Option<Vec<u8>>, TtsError> {
+    let file = match vfs::open_file(path, false, None) {
+        Ok(file) => file,
+        Err(_) => return Ok(None),
+    };
+    let bytes = file.read().map_err(|e| TtsError::Vfs(e.to_string()))?;
+    Ok(Some(bytes).filter(|bytes| !bytes.is_empty()))
+}
+
+// JSON state saved with `write_file`; a missing or empty file reads as the default value
+pub(crate) fn load_json_or_default<T: DeserializeOwned + Default>(
+    path: &str,
+) -> Result<T, TtsError> {
+    match read_file(path)? {
+        Some(bytes) => serde_json::from_slice(&bytes)
+            .map_err(|e| TtsError::DeserializationError(e.to_string())),
+        None => Ok(T::default()),
+    }
+}
+
+// the KV API reports a missing key as an error, so any failed lookup reads as absent
+pub(crate) fn kv_get<K, V>(kv: &Kv<K, V>, key: &K) -> Option<V>
+where
+    K: Serialize + DeserializeOwned,
+    V: Serialize + DeserializeOwned,
+{
+    kv.get(key).ok()
+}
+
+pub(crate) fn write_file(path: &str, bytes: &[u8]) -> Result<(), TtsError> {
+    let file = vfs::open_file(path, true, None).map_err(|e| TtsError::Vfs(e.to_string()))?;
+    file.write(bytes).map_err(|e| TtsError::Vfs(e.to_string()))
+}
+
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+pub(crate) fn now_secs() -> u64 {
+    now_ms() / 1000
+}
+
+// the requested number of requests in flight, capped at the subscription tier's limit when
+// the account's capabilities are known
+pub(crate) fn capped_concurrency(
+    requested: usize,
+    capabilities: Option<&AccountCapabilities>,
+) -> usize {
+    capabilities
+        .and_then(AccountCapabilities::concurrency_limit)
+        .map_or(requested, |limit| requested.min(limit))
+}
@@ -0,0 +1,427 @@
+use regex::{Captures, Regex};
+use std::sync::OnceLock;
+
+const ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+const SCALES: [&str; 7] = [
+    "",
+    "thousand",
+    "million",
+    "billion",
+    "trillion",
+    "quadrillion",
+    "quintillion",
+];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+// (abbreviation, singular, plural)
+const UNITS: [(&str, &str, &str); 24] = [
+    ("km/h", "kilometer per hour", "kilometers per hour"),
+    ("mph", "mile per hour", "miles per hour"),
+    ("km", "kilometer", "kilometers"),
+    ("kg", "kilogram", "kilograms"),
+    ("mg", "milligram", "milligrams"),
+    ("cm", "centimeter", "centimeters"),
+    ("mm", "millimeter", "millimeters"),
+    ("ml", "milliliter", "milliliters"),
+    ("TB", "terabyte", "terabytes"),
+    ("GB", "gigabyte", "gigabytes"),
+    ("MB", "megabyte", "megabytes"),
+    ("KB", "kilobyte", "kilobytes"),
+    ("ms", "millisecond", "milliseconds"),
+    ("min", "minute", "minutes"),
+    ("lbs", "pound", "pounds"),
+    ("lb", "pound", "pounds"),
+    ("ft", "foot", "feet"),
+    ("mi", "mile", "miles"),
+    ("°C", "degree Celsius", "degrees Celsius"),
+    ("°F", "degree Fahrenheit", "degrees Fahrenheit"),
+    ("m", "meter", "meters"),
+    ("g", "gram", "grams"),
+    ("h", "hour", "hours"),
+    ("%", "percent", "percent"),
+];
+
+fn pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(concat!(
+            r"(?P<date>\b(?P<year>\d{4})-(?P<month>\d{2})-(?P<day>\d{2})\b)",
+            r"|(?P<currency>[$€£])(?P<amount>\d{1,3}(?:,\d{3})+(?:\.\d+)?|\d+(?:\.\d+)?)",
+            r"|(?P<minus>-)?\b(?P<number>\d{1,3}(?:,\d{3})+(?:\.\d+)?|\d+(?:\.\d+)?)",
+            r"(?P<suffix>%|°C|°F|(?:st|nd|rd|th)\b",
+            r"|\s?(?:km/h|mph|km|kg|mg|cm|mm|ml|TB|GB|MB|KB|ms|min|lbs|lb|ft|mi)\b",
+            r"|(?:m|g|h)\b)?",
+        ))
+        .expect("verbalization pattern is valid")
+    })
+}
+
+// expands numbers, currency amounts, ISO dates, and units into English words;
+// text in other languages is returned unchanged
+pub fn verbalize(text: &str, language: Option<&str>) -> String {
+    let language = language.map(|l| l.to_ascii_lowercase());
+    let british = match language.as_deref() {
+        None => false,
+        Some(l) if l == "en" || l.starts_with("en-") || l.starts_with("en_") => {
+            l.ends_with("gb") || l.ends_with("au") || l.ends_with("ie") || l.ends_with("nz")
+        }
+        Some(_) => return text.to_string(),
+    };
+
+    pattern()
+        .replace_all(text, |caps: &Captures| {
+            let whole = caps.get(0).map_or("", |m| m.as_str());
+            if caps.name("date").is_some() {
+                return verbalize_date(caps, british).unwrap_or_else(|| whole.to_string());
+            }
+            if let Some(currency) = caps.name("currency") {
+                return verbalize_currency(currency.as_str(), &caps["amount"], british);
+            }
+
+            let number = &caps["number"];
+            let mut spoken = String::new();
+            // a hyphen after a word character is a compound like "COVID-19", not a sign
+            if let Some(minus) = caps.name("minus") {
+                let preceded_by_word = text[..minus.start()]
+                    .chars()
+                    .next_back()
+                    .is_some_and(|c| c.is_alphanumeric());
+                if preceded_by_word {
+                    spoken.push('-');
+                } else {
+                    spoken.push_str("minus ");
+                }
+            }
+
+            match caps.name("suffix").map(|s| s.as_str().trim_start()) {
+                Some(ordinal @ ("st" | "nd" | "rd" | "th")) => match parse_integer(number) {
+                    Some(n) => spoken.push_str(&ordinal_words(n, british)),
+                    None => {
+                        spoken.push_str(&number_words(number, british));
+                        spoken.push_str(ordinal);
+                    }
+                },
+                Some(unit) => {
+                    let (_, singular, plural) = UNITS
+                        .iter()
+                        .find(|(abbreviation, _, _)| *abbreviation == unit)
+                        .copied()
+                        .unwrap_or((unit, unit, unit));
+                    spoken.push_str(&number_words(number, british));
+                    spoken.push(' ');
+                    spoken.push_str(if number == "1" { singular } else { plural });
+                }
+                // a bare four-digit number reads as a year ("nineteen eighty-four")
+                None if number.len() == 4 && number.bytes().all(|b| b.is_ascii_digit()) => {
+                    let n = parse_integer(number).unwrap_or_default();
+                    spoken.push_str(&year_words(n, british));
+                }
+                None => spoken.push_str(&number_words(number, british)),
+            }
+            spoken
+        })
+        .into_owned()
+}
+
+pub fn cardinal_words(n: u64, british: bool) -> String {
+    if n == 0 {
+        return ONES[0].to_string();
+    }
+
+    let mut groups = Vec::new();
+    let mut rest = n;
+    while rest > 0 {
+        groups.push((rest % 1000) as usize);
+        rest /= 1000;
+    }
+
+    let mut words: Vec<String> = Vec::new();
+    for (scale, group) in groups.iter().enumerate().rev() {
+        if *group == 0 {
+            continue;
+        }
+        // British English joins a trailing group below one hundred with "and"
+        if british && scale == 0 && *group < 100 && !words.is_empty() {
+            words.push("and".to_string());
+        }
+        words.push(group_words(*group, british));
+        if scale > 0 {
+            words.push(SCALES[scale].to_string());
+        }
+    }
+
+    words.join(" ")
+}
+
+pub fn ordinal_words(n: u64, british: bool) -> String {
+    let cardinal = cardinal_words(n, british);
+    let split = cardinal.rfind([' ', '-']).map_or(0, |i| i + 1);
+    let (head, last) = cardinal.split_at(split);
+
+    let last = match last {
+        "one" => "first".to_string(),
+        "two" => "second".to_string(),
+        "three" => "third".to_string(),
+        "five" => "fifth".to_string(),
+        "eight" => "eighth".to_string(),
+        "nine" => "ninth".to_string(),
+        "twelve" => "twelfth".to_string(),
+        word if word.ends_with('y') => format!("{}ieth", &word[..word.len() - 1]),
+        word => format!("{}th", word),
+    };
+
+    format!("{}{}", head, last)
+}
+
+fn group_words(n: usize, british: bool) -> String {
+    let hundreds = n / 100;
+    let rest = n % 100;
+    let mut words = Vec::new();
+
+    if hundreds > 0 {
+        words.push(format!("{} hundred", ONES[hundreds]));
+        if british && rest > 0 {
+            words.push("and".to_string());
+        }
+    }
+    if rest >= 20 {
+        let tens = TENS[rest / 10];
+        if rest % 10 == 0 {
+            words.push(tens.to_string());
+        } else {
+            words.push(format!("{}-{}", tens, ONES[rest % 10]));
+        }
+    } else if rest > 0 {
+        words.push(ONES[rest].to_string());
+    }
+
+    words.join(" ")
+}
+
+fn parse_integer(number: &str) -> Option<u64> {
+    number.replace(',', "").parse().ok()
+}
+
+fn digit_words(digits: &str) -> String {
+    digits
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|d| ONES[d as usize])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn number_words(number: &str, british: bool) -> String {
+    let (integer, fraction) = match number.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (number, None),
+    };
+
+    let mut words = match parse_integer(integer) {
+        Some(n) => cardinal_words(n, british),
+        None => digit_words(integer),
+    };
+    if let Some(fraction) = fraction {
+        words.push_str(" point ");
+        words.push_str(&digit_words(fraction));
+    }
+    words
+}
+
+fn verbalize_currency(symbol: &str, amount: &str, british: bool) -> String {
+    let (major_singular, major_plural, minor_singular, minor_plural) = match symbol {
+        "€" => ("euro", "euros", "cent", "cents"),
+        "£" => ("pound", "pounds", "penny", "pence"),
+        _ => ("dollar", "dollars", "cent", "cents"),
+    };
+
+    let (integer, fraction) = match amount.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (amount, None),
+    };
+
+    let Some(major) = parse_integer(integer) else {
+        return format!("{} {}", number_words(amount, british), major_plural);
+    };
+    let minor = match fraction {
+        None => 0,
+        Some(f) if f.len() <= 2 => format!("{:0<2}", f).parse().unwrap_or(0),
+        Some(_) => return format!("{} {}", number_words(amount, british), major_plural),
+    };
+
+    let major_words = format!(
+        "{} {}",
+        cardinal_words(major, british),
+        if major == 1 {
+            major_singular
+        } else {
+            major_plural
+        }
+    );
+    let minor_words = format!(
+        "{} {}",
+        cardinal_words(minor, british),
+        if minor == 1 {
+            minor_singular
+        } else {
+            minor_plural
+        }
+    );
+
+    match (major, minor) {
+        (_, 0) => major_words,
+        (0, _) => minor_words,
+        _ => format!("{} and {}", major_words, minor_words),
+    }
+}
+
+fn verbalize_date(caps: &Captures, british: bool) -> Option<String> {
+    let year: u64 = caps["year"].parse().ok()?;
+    let month: usize = caps["month"].parse().ok()?;
+    let day: u64 = caps["day"].parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let month = MONTHS[month - 1];
+    let day = ordinal_words(day, british);
+    let year = year_words(year, british);
+    if british {
+        Some(format!("the {} of {} {}", day, month, year))
+    } else {
+        Some(format!("{} {}, {}", month, day, year))
+    }
+}
+
+fn year_words(year: u64, british: bool) -> String {
+    if !(1100..=2099).contains(&year) || (2000..=2009).contains(&year) {
+        return cardinal_words(year, british);
+    }
+
+    let century = cardinal_words(year / 100, british);
+    match year % 100 {
+        0 => format!("{} hundred", century),
+        rest @ 1..=9 => format!("{} oh {}", century, ONES[rest as usize]),
+        rest => format!("{} {}", century, cardinal_words(rest, british)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cardinals() {
+        assert_eq!(cardinal_words(0, false), "zero");
+        assert_eq!(cardinal_words(21, false), "twenty-one");
+        assert_eq!(cardinal_words(1_000_001, false), "one million one");
+        assert_eq!(cardinal_words(101, true), "one hundred and one");
+        assert_eq!(cardinal_words(1_001, true), "one thousand and one");
+    }
+
+    #[test]
+    fn ordinals() {
+        assert_eq!(ordinal_words(1, false), "first");
+        assert_eq!(ordinal_words(12, false), "twelfth");
+        assert_eq!(ordinal_words(21, false), "twenty-first");
+        assert_eq!(ordinal_words(40, false), "fortieth");
+        assert_eq!(ordinal_words(100, false), "one hundredth");
+    }
+
+    #[test]
+    fn years() {
+        assert_eq!(year_words(1984, false), "nineteen eighty-four");
+        assert_eq!(year_words(1900, false), "nineteen hundred");
+        assert_eq!(year_words(1905, false), "nineteen oh five");
+        assert_eq!(year_words(2005, false), "two thousand five");
+    }
+
+    #[test]
+    fn currency_amounts() {
+        assert_eq!(
+            verbalize_currency("$", "1.05", false),
+            "one dollar and five cents"
+        );
+        assert_eq!(verbalize_currency("£", "0.50", false), "fifty pence");
+        assert_eq!(verbalize_currency("€", "2", false), "two euros");
+    }
+
+    #[test]
+    fn verbalizes_empty_and_unsupported_languages_unchanged() {
+        assert_eq!(verbalize("", None), "");
+        assert_eq!(verbalize("5 km", Some("fr")), "5 km");
+    }
+
+    #[test]
+    fn verbalizes_numbers_units_and_currency() {
+        assert_eq!(
+            verbalize("It is 5km away, 20°C, in 1984", Some("en-US")),
+            "It is five kilometers away, twenty degrees Celsius, in nineteen eighty-four"
+        );
+        assert_eq!(
+            verbalize("$1,250.50 for the 3rd café", None),
+            "one thousand two hundred fifty dollars and fifty cents for the third café"
+        );
+        assert_eq!(verbalize("pi is 3.14", None), "pi is three point one four");
+    }
+
+    #[test]
+    fn verbalizes_signs_and_compounds() {
+        assert_eq!(verbalize("-5 degrees", None), "minus five degrees");
+        assert_eq!(verbalize("COVID-19", None), "COVID-nineteen");
+    }
+
+    #[test]
+    fn verbalizes_dates() {
+        assert_eq!(
+            verbalize("2024-03-05", None),
+            "March fifth, twenty twenty-four"
+        );
+        assert_eq!(
+            verbalize("2024-03-05", Some("en-GB")),
+            "the fifth of March twenty twenty-four"
+        );
+        // an impossible month is left as written
+        assert_eq!(verbalize("2024-13-05", None), "2024-13-05");
+    }
+}
@@ -0,0 +1,232 @@
+use crate::error::TtsError;
+use crate::types::Alignment;
+use serde::{Deserialize, Serialize};
+
+// Oculus-style viseme set; digraphs come before their first letter so they match first
+const DEFAULT_VISEMES: [(&str, &str); 29] = [
+    ("th", "TH"),
+    ("ch", "CH"),
+    ("sh", "CH"),
+    ("ck", "kk"),
+    ("ph", "FF"),
+    ("p", "PP"),
+    ("b", "PP"),
+    ("m", "PP"),
+    ("f", "FF"),
+    ("v", "FF"),
+    ("t", "DD"),
+    ("d", "DD"),
+    ("k", "kk"),
+    ("g", "kk"),
+    ("c", "kk"),
+    ("q", "kk"),
+    ("j", "CH"),
+    ("s", "SS"),
+    ("z", "SS"),
+    ("x", "SS"),
+    ("n", "nn"),
+    ("l", "nn"),
+    ("r", "RR"),
+    ("a", "aa"),
+    ("e", "E"),
+    ("i", "I"),
+    ("y", "I"),
+    ("o", "O"),
+    ("u", "U"),
+];
+const DEFAULT_SILENCE: &str = "sil";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VisemeCue {
+    pub viseme: String,
+    pub start_seconds: f64,
+    pub end_seconds: f64,
+}
+
+// maps letter sequences (matched case-insensitively, longest first) to viseme names;
+// whitespace and punctuation map to the silence viseme, and letters with no mapping (like
+// a silent "h") extend the viseme before them
+#[derive(Debug, Clone)]
+pub struct VisemeSet {
+    mappings: Vec<(String, String)>,
+    silence: String,
+}
+
+impl Default for VisemeSet {
+    fn default() -> Self {
+        let mut set = Self::empty(DEFAULT_SILENCE);
+        for (letters, viseme) in DEFAULT_VISEMES {
+            set = set.map(letters, viseme);
+        }
+        set
+    }
+}
+
+impl VisemeSet {
+    pub fn empty(silence: impl Into<String>) -> Self {
+        Self {
+            mappings: Vec::new(),
+            silence: silence.into(),
+        }
+    }
+
+    // adds a mapping, or replaces the viseme of an existing one; empty letters would match
+    // everywhere without consuming anything, so they are ignored
+    pub fn map(mut self, letters: impl Into<String>, viseme: impl Into<String>) -> Self {
+        let letters = letters.into().to_lowercase();
+        if letters.is_empty() {
+            return self;
+        }
+        let viseme = viseme.into();
+        match self.mappings.iter_mut().find(|(l, _)| *l == letters) {
+            Some(mapping) => mapping.1 = viseme,
+            None => self.mappings.push((letters, viseme)),
+        }
+        self.mappings
+            .sort_by_key(|(letters, _)| std::cmp::Reverse(letters.chars().count()));
+        self
+    }
+
+    pub fn silence(mut self, viseme: impl Into<String>) -> Self {
+        self.silence = viseme.into();
+        self
+    }
+
+    // the viseme for the letters starting at `characters[0]` and how many characters it spans
+    fn lookup(&self, characters: &[String]) -> Option<(&str, usize)> {
+        self.mappings.iter().find_map(|(letters, viseme)| {
+            let len = letters.chars().count();
+            let candidate: String = characters
+                .iter()
+                .take(len)
+                .map(|c| c.to_lowercase())
+                .collect();
+            (characters.len() >= len && candidate == *letters).then_some((viseme.as_str(), len))
+        })
+    }
+}
+
+// a timeline of visemes covering the alignment, with consecutive repeats merged
+pub fn viseme_track(alignment: &Alignment, set: &VisemeSet) -> Vec<VisemeCue> {
+    let characters = &alignment.characters;
+    let starts = &alignment.character_start_times_seconds;
+    let ends = &alignment.character_end_times_seconds;
+    let count = characters.len().min(starts.len()).min(ends.len());
+
+    let mut track: Vec<VisemeCue> = Vec::new();
+    let mut index = 0;
+    while index < count {
+        let character = &characters[index];
+        let (viseme, span) = if !character.chars().any(char::is_alphanumeric) {
+            (Some(set.silence.as_str()), 1)
+        } else {
+            match set.lookup(&characters[index..count]) {
+                Some((viseme, span)) => (Some(viseme), span),
+                None => (None, 1),
+            }
+        };
+        let start = starts[index];
+        let end = ends[index + span - 1];
+        index += span;
+
+        match (viseme, track.last_mut()) {
+            (Some(viseme), Some(last)) if last.viseme == viseme => last.end_seconds = end,
+            (None, Some(last)) => last.end_seconds = end,
+            (Some(viseme), _) => track.push(VisemeCue {
+                viseme: viseme.to_string(),
+                start_seconds: start,
+                end_seconds: end,
+            }),
+            (None, None) => track.push(VisemeCue {
+                viseme: set.silence.clone(),
+                start_seconds: start,
+                end_seconds: end,
+            }),
+        }
+    }
+    track
+}
+
+pub fn viseme_track_json(alignment: &Alignment, set: &VisemeSet) -> Result<String, TtsError> {
+    serde_json::to_string(&viseme_track(alignment, set))
+        .map_err(|e| TtsError::SerializationError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // one second per character
+    fn alignment(text: &str) -> Alignment {
+        let characters: Vec<String> = text.chars().map(String::from).collect();
+        Alignment {
+            character_start_times_seconds: (0..characters.len()).map(|i| i as f64).collect(),
+            character_end_times_seconds: (1..=characters.len()).map(|i| i as f64).collect(),
+            characters,
+        }
+    }
+
+    fn track(text: &str, set: &VisemeSet) -> Vec<(String, f64, f64)> {
+        viseme_track(&alignment(text), set)
+            .into_iter()
+            .map(|cue| (cue.viseme, cue.start_seconds, cue.end_seconds))
+            .collect()
+    }
+
+    fn cue(viseme: &str, start: f64, end: f64) -> (String, f64, f64) {
+        (viseme.to_string(), start, end)
+    }
+
+    #[test]
+    fn digraphs_match_before_single_letters() {
+        let set = VisemeSet::default();
+        assert_eq!(track("Th", &set), [cue("TH", 0.0, 2.0)]);
+        assert_eq!(
+            track("at", &set),
+            [cue("aa", 0.0, 1.0), cue("DD", 1.0, 2.0)]
+        );
+    }
+
+    #[test]
+    fn silent_letters_extend_the_previous_viseme() {
+        let set = VisemeSet::default();
+        assert_eq!(track("oh", &set), [cue("O", 0.0, 2.0)]);
+        // with nothing before it, an unmapped letter reads as silence
+        assert_eq!(
+            track("ho", &set),
+            [cue("sil", 0.0, 1.0), cue("O", 1.0, 2.0)]
+        );
+    }
+
+    #[test]
+    fn repeats_and_pauses_merge() {
+        let set = VisemeSet::default();
+        assert_eq!(
+            track("mb, a", &set),
+            [
+                cue("PP", 0.0, 2.0),
+                cue("sil", 2.0, 4.0),
+                cue("aa", 4.0, 5.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_sets() {
+        let set = VisemeSet::empty("rest").map("A", "open").map("a", "wide");
+        assert_eq!(
+            track("a b", &set),
+            [cue("wide", 0.0, 1.0), cue("rest", 1.0, 3.0)]
+        );
+    }
+
+    #[test]
+    fn empty_letters_are_ignored() {
+        let set = VisemeSet::default().map("", "X");
+        assert_eq!(
+            track("ba", &set),
+            [cue("PP", 0.0, 1.0), cue("aa", 1.0, 2.0)]
+        );
+        assert!(track("", &set).is_empty());
+    }
+}
@@ -0,0 +1,45 @@
+use crate::account::AccountCapabilities;
+use crate::audio::{decode_pcm16, pcm_sample_rate, resample, ResampleQuality};
+use crate::types::AudioFormat;
+
+// what voice-chat SDK sinks take: 20 ms of 48 kHz stereo, samples interleaved left/right
+pub const VOICE_CHAT_SAMPLE_RATE: u32 = 48000;
+pub const VOICE_CHAT_CHANNELS: usize = 2;
+pub const VOICE_CHAT_FRAME_MS: u64 = 20;
+pub const VOICE_CHAT_FRAME_LEN: usize =
+    (VOICE_CHAT_SAMPLE_RATE as u64 * VOICE_CHAT_FRAME_MS / 1000) as usize * VOICE_CHAT_CHANNELS;
+
+// the highest-rate PCM format the account may request; without known capabilities, the
+// highest rate open to every tier
+pub fn best_pcm_format(capabilities: Option<&AccountCapabilities>) -> AudioFormat {
+    match capabilities {
+        Some(capabilities) if capabilities.format_allowed(&AudioFormat::Pcm44100) => {
+            AudioFormat::Pcm44100
+        }
+        _ => AudioFormat::Pcm24000,
+    }
+}
+
+// converts mono PCM in `format` to 48 kHz stereo frames; the last frame is padded with silence
+pub fn voice_chat_frames(pcm: &[u8], format: &AudioFormat) -> Vec<Vec<i16>> {
+    let rate = pcm_sample_rate(format).unwrap_or(VOICE_CHAT_SAMPLE_RATE);
+    let mono = resample(
+        &decode_pcm16(pcm),
+        rate,
+        VOICE_CHAT_SAMPLE_RATE,
+        ResampleQuality::Balanced,
+    );
+    let stereo: Vec<i16> = mono
+        .iter()
+        .flat_map(|sample| [*sample; VOICE_CHAT_CHANNELS])
+        .collect();
+
+    stereo
+        .chunks(VOICE_CHAT_FRAME_LEN)
+        .map(|chunk| {
+            let mut frame = chunk.to_vec();
+            frame.resize(VOICE_CHAT_FRAME_LEN, 0);
+            frame
+        })
+        .collect()
+}
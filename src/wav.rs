@@ -0,0 +1,222 @@
+use crate::audio::pcm_sample_rate;
+use crate::error::TtsError;
+use crate::types::{Alignment, SpeechResponse};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CueGranularity {
+    Word,
+    Sentence,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WavCue {
+    pub sample_offset: u32,
+    pub label: String,
+}
+
+// wraps the response's PCM in a WAV container, with a cue marker at each word or sentence
+// boundary when `cues` is set and the response carries alignment
+pub fn response_to_wav(
+    response: &SpeechResponse,
+    cues: Option<CueGranularity>,
+) -> Result<Vec<u8>, TtsError> {
+    let sample_rate = pcm_sample_rate(&response.format)
+        .ok_or_else(|| TtsError::WavUnsupportedFormat(response.format.as_str().to_string()))?;
+    let alignment = response
+        .alignment
+        .as_ref()
+        .or(response.normalized_alignment.as_ref());
+    let cues = match (cues, alignment) {
+        (Some(granularity), Some(alignment)) => alignment_cues(alignment, granularity, sample_rate),
+        _ => Vec::new(),
+    };
+    Ok(pcm_to_wav(&response.audio_data, sample_rate, &cues))
+}
+
+// a cue at the first character of each word or sentence, labelled with its text
+pub fn alignment_cues(
+    alignment: &Alignment,
+    granularity: CueGranularity,
+    sample_rate: u32,
+) -> Vec<WavCue> {
+    let mut cues: Vec<WavCue> = Vec::new();
+    let mut at_boundary = true;
+    let mut previous = "";
+
+    for (character, start) in alignment
+        .characters
+        .iter()
+        .zip(&alignment.character_start_times_seconds)
+    {
+        let blank = character.trim().is_empty();
+        if blank {
+            if granularity == CueGranularity::Word || previous.ends_with(['.', '!', '?']) {
+                at_boundary = true;
+            }
+        } else if at_boundary {
+            cues.push(WavCue {
+                sample_offset: (start * sample_rate as f64).round() as u32,
+                label: String::new(),
+            });
+            at_boundary = false;
+        }
+        if let Some(cue) = cues.last_mut() {
+            if !(blank && at_boundary) {
+                cue.label.push_str(character);
+            }
+        }
+        if !blank {
+            previous = character;
+        }
+    }
+
+    for cue in &mut cues {
+        cue.label = cue.label.trim().to_string();
+    }
+    cues
+}
+
+// 16-bit mono PCM in a RIFF/WAVE container; cues go in a `cue ` chunk with their labels in
+// a LIST/adtl chunk, which is where DAWs and game audio tools look for markers
+pub fn pcm_to_wav(pcm: &[u8], sample_rate: u32, cues: &[WavCue]) -> Vec<u8> {
+    let mut format = Vec::with_capacity(16);
+    format.extend_from_slice(&1u16.to_le_bytes()); // integer PCM
+    format.extend_from_slice(&1u16.to_le_bytes()); // mono
+    format.extend_from_slice(&sample_rate.to_le_bytes());
+    format.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+    format.extend_from_slice(&2u16.to_le_bytes());
+    format.extend_from_slice(&16u16.to_le_bytes());
+
+    let mut body = b"WAVE".to_vec();
+    push_chunk(&mut body, b"fmt ", &format);
+
+    if !cues.is_empty() {
+        let mut cue_points = (cues.len() as u32).to_le_bytes().to_vec();
+        let mut labels = b"adtl".to_vec();
+        for (index, cue) in cues.iter().enumerate() {
+            let id = index as u32 + 1;
+            cue_points.extend_from_slice(&id.to_le_bytes());
+            cue_points.extend_from_slice(&cue.sample_offset.to_le_bytes());
+            cue_points.extend_from_slice(b"data");
+            cue_points.extend_from_slice(&0u32.to_le_bytes());
+            cue_points.extend_from_slice(&0u32.to_le_bytes());
+            cue_points.extend_from_slice(&cue.sample_offset.to_le_bytes());
+
+            let mut label = id.to_le_bytes().to_vec();
+            label.extend_from_slice(cue.label.as_bytes());
+            label.push(0);
+            push_chunk(&mut labels, b"labl", &label);
+        }
+        push_chunk(&mut body, b"cue ", &cue_points);
+        push_chunk(&mut body, b"LIST", &labels);
+    }
+
+    push_chunk(&mut body, b"data", pcm);
+
+    let mut wav = b"RIFF".to_vec();
+    wav.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    wav.extend_from_slice(&body);
+    wav
+}
+
+// chunks are word-aligned: odd-sized payloads get a pad byte not counted in the size
+fn push_chunk(out: &mut Vec<u8>, id: &[u8; 4], payload: &[u8]) {
+    out.extend_from_slice(id);
+    out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    out.extend_from_slice(payload);
+    if payload.len() % 2 == 1 {
+        out.push(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alignment(text: &str) -> Alignment {
+        let characters: Vec<String> = text.chars().map(String::from).collect();
+        let starts: Vec<f64> = (0..characters.len()).map(|i| i as f64 * 0.1).collect();
+        Alignment {
+            character_end_times_seconds: starts.iter().map(|s| s + 0.1).collect(),
+            character_start_times_seconds: starts,
+            characters,
+        }
+    }
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn header_layout() {
+        let wav = pcm_to_wav(&[1, 2, 3], 16000, &[]);
+        assert_eq!(wav.len(), 48);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(u32_at(&wav, 4), 40);
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(u32_at(&wav, 16), 16);
+        assert_eq!(&wav[20..24], &[1, 0, 1, 0]);
+        assert_eq!(u32_at(&wav, 24), 16000);
+        assert_eq!(u32_at(&wav, 28), 32000);
+        assert_eq!(&wav[32..36], &[2, 0, 16, 0]);
+        assert_eq!(&wav[36..40], b"data");
+        // the odd-sized data chunk is padded, and the pad isn't counted
+        assert_eq!(u32_at(&wav, 40), 3);
+        assert_eq!(&wav[44..], &[1, 2, 3, 0]);
+    }
+
+    #[test]
+    fn empty_pcm() {
+        let wav = pcm_to_wav(&[], 22050, &[]);
+        assert_eq!(wav.len(), 44);
+        assert_eq!(u32_at(&wav, 40), 0);
+    }
+
+    #[test]
+    fn cue_and_label_chunks() {
+        let cue = WavCue {
+            sample_offset: 160,
+            label: "Hi".to_string(),
+        };
+        let wav = pcm_to_wav(&[], 16000, &[cue]);
+        assert_eq!(wav.len(), 108);
+        assert_eq!(u32_at(&wav, 4), 100);
+
+        assert_eq!(&wav[36..40], b"cue ");
+        assert_eq!(u32_at(&wav, 40), 28);
+        assert_eq!(u32_at(&wav, 44), 1);
+        assert_eq!(u32_at(&wav, 48), 1);
+        assert_eq!(u32_at(&wav, 52), 160);
+        assert_eq!(&wav[56..60], b"data");
+        assert_eq!(u32_at(&wav, 68), 160);
+
+        assert_eq!(&wav[72..76], b"LIST");
+        assert_eq!(u32_at(&wav, 76), 20);
+        assert_eq!(&wav[80..88], b"adtllabl");
+        assert_eq!(u32_at(&wav, 88), 7);
+        assert_eq!(u32_at(&wav, 92), 1);
+        assert_eq!(&wav[96..100], b"Hi\0\0");
+        assert_eq!(&wav[100..104], b"data");
+    }
+
+    #[test]
+    fn word_and_sentence_cues() {
+        let alignment = alignment("Hi thére. Bye");
+        let words = alignment_cues(&alignment, CueGranularity::Word, 1000);
+        let labels: Vec<&str> = words.iter().map(|cue| cue.label.as_str()).collect();
+        let offsets: Vec<u32> = words.iter().map(|cue| cue.sample_offset).collect();
+        assert_eq!(labels, ["Hi", "thére.", "Bye"]);
+        assert_eq!(offsets, [0, 300, 1000]);
+
+        let sentences = alignment_cues(&alignment, CueGranularity::Sentence, 1000);
+        let labels: Vec<&str> = sentences.iter().map(|cue| cue.label.as_str()).collect();
+        assert_eq!(labels, ["Hi thére.", "Bye"]);
+        assert_eq!(sentences[1].sample_offset, 1000);
+    }
+
+    #[test]
+    fn no_cues_without_characters() {
+        assert!(alignment_cues(&alignment(""), CueGranularity::Word, 16000).is_empty());
+        assert!(alignment_cues(&alignment("   "), CueGranularity::Sentence, 16000).is_empty());
+    }
+}
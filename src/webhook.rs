@@ -0,0 +1,260 @@
+use crate::error::TtsError;
+use crate::hash::{constant_time_eq, from_hex, hmac_sha256};
+use crate::util::now_secs;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+pub const SIGNATURE_HEADER: &str = "elevenlabs-signature";
+// matches the replay window used by the official ElevenLabs SDKs
+const DEFAULT_TOLERANCE_SECS: u64 = 30 * 60;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookEvent {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub event_timestamp: Option<u64>,
+    #[serde(default)]
+    pub data: Value,
+}
+
+impl WebhookEvent {
+    pub fn data_as<T: DeserializeOwned>(&self) -> Result<T, TtsError> {
+        serde_json::from_value(self.data.clone())
+            .map_err(|e| TtsError::DeserializationError(e.to_string()))
+    }
+}
+
+type Callback = Box<dyn Fn(&WebhookEvent)>;
+
+pub struct WebhookHandler {
+    secret: String,
+    tolerance_secs: u64,
+    callbacks: HashMap<String, Vec<Callback>>,
+    catch_all: Vec<Callback>,
+}
+
+impl WebhookHandler {
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self {
+            secret: secret.into(),
+            tolerance_secs: DEFAULT_TOLERANCE_SECS,
+            callbacks: HashMap::new(),
+            catch_all: Vec::new(),
+        }
+    }
+
+    pub fn with_tolerance(mut self, tolerance_secs: u64) -> Self {
+        self.tolerance_secs = tolerance_secs;
+        self
+    }
+
+    pub fn on(
+        mut self,
+        event_type: impl Into<String>,
+        callback: impl Fn(&WebhookEvent) + 'static,
+    ) -> Self {
+        self.callbacks
+            .entry(event_type.into())
+            .or_default()
+            .push(Box::new(callback));
+        self
+    }
+
+    pub fn on_any(mut self, callback: impl Fn(&WebhookEvent) + 'static) -> Self {
+        self.catch_all.push(Box::new(callback));
+        self
+    }
+
+    pub fn verify(&self, signature_header: &str, body: &[u8]) -> Result<(), TtsError> {
+        self.verify_at(signature_header, body, now_secs())
+    }
+
+    // header format: "t=<unix seconds>,v0=<hex HMAC-SHA256 of "<t>.<body>">"
+    pub fn verify_at(
+        &self,
+        signature_header: &str,
+        body: &[u8],
+        now_secs: u64,
+    ) -> Result<(), TtsError> {
+        let mut timestamp = None;
+        let mut signatures = Vec::new();
+        for part in signature_header.split(',') {
+            match part.trim().split_once('=') {
+                Some(("t", value)) => timestamp = Some(value),
+                Some(("v0", value)) => signatures.push(value),
+                _ => {}
+            }
+        }
+
+        let timestamp = timestamp
+            .ok_or_else(|| TtsError::InvalidWebhookSignature("missing timestamp".to_string()))?;
+        let sent_at: u64 = timestamp
+            .parse()
+            .map_err(|_| TtsError::InvalidWebhookSignature("malformed timestamp".to_string()))?;
+        if now_secs.abs_diff(sent_at) > self.tolerance_secs {
+            return Err(TtsError::InvalidWebhookSignature(
+                "timestamp outside tolerance".to_string(),
+            ));
+        }
+
+        let mut message = format!("{}.", timestamp).into_bytes();
+        message.extend_from_slice(body);
+        let expected = hmac_sha256(self.secret.as_bytes(), &message);
+
+        let valid = signatures
+            .iter()
+            .filter_map(|signature| from_hex(signature))
+            .any(|signature| constant_time_eq(&signature, &expected));
+        if valid {
+            Ok(())
+        } else {
+            Err(TtsError::InvalidWebhookSignature(
+                "signature mismatch".to_string(),
+            ))
+        }
+    }
+
+    // verifies the request, parses the event, and runs the matching callbacks
+    pub fn handle(
+        &self,
+        signature_header: Option<&str>,
+        body: &[u8],
+    ) -> Result<WebhookEvent, TtsError> {
+        let signature_header = signature_header
+            .ok_or_else(|| TtsError::InvalidWebhookSignature("missing header".to_string()))?;
+        self.verify(signature_header, body)?;
+
+        let event: WebhookEvent = serde_json::from_slice(body)
+            .map_err(|e| TtsError::DeserializationError(e.to_string()))?;
+        for callback in self.callbacks.get(&event.event_type).into_iter().flatten() {
+            callback(&event);
+        }
+        for callback in &self.catch_all {
+            callback(&event);
+        }
+        Ok(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::to_hex;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    const SECRET: &str = "whsec_test";
+    const SENT_AT: u64 = 1_700_000_000;
+    const BODY: &[u8] = br#"{"type":"post_call_transcription","data":{}}"#;
+    const SIGNATURE: &str = "34ec4313d7a937979badafecf8826902c6dbb8f28018a1f3ca8b87fa18d4a77a";
+
+    fn rejection(result: Result<(), TtsError>) -> String {
+        match result {
+            Err(TtsError::InvalidWebhookSignature(reason)) => reason,
+            other => panic!("expected a signature error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn hmac_known_answer() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            to_hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn accepts_a_valid_signature() {
+        let handler = WebhookHandler::new(SECRET);
+        let header = format!("t={},v0={}", SENT_AT, SIGNATURE);
+        assert!(handler.verify_at(&header, BODY, SENT_AT).is_ok());
+        // any one matching signature is enough
+        let header = format!("t={}, v0=00, v0={}", SENT_AT, SIGNATURE);
+        assert!(handler.verify_at(&header, BODY, SENT_AT + 60).is_ok());
+    }
+
+    #[test]
+    fn rejects_a_tampered_body_or_wrong_secret() {
+        let header = format!("t={},v0={}", SENT_AT, SIGNATURE);
+        let handler = WebhookHandler::new(SECRET);
+        assert_eq!(
+            rejection(handler.verify_at(&header, b"{}", SENT_AT)),
+            "signature mismatch"
+        );
+        let handler = WebhookHandler::new("another secret");
+        assert_eq!(
+            rejection(handler.verify_at(&header, BODY, SENT_AT)),
+            "signature mismatch"
+        );
+    }
+
+    #[test]
+    fn rejects_stale_timestamps() {
+        let handler = WebhookHandler::new(SECRET).with_tolerance(60);
+        let header = format!("t={},v0={}", SENT_AT, SIGNATURE);
+        assert_eq!(
+            rejection(handler.verify_at(&header, BODY, SENT_AT + 61)),
+            "timestamp outside tolerance"
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_headers() {
+        let handler = WebhookHandler::new(SECRET);
+        assert_eq!(
+            rejection(handler.verify_at("", BODY, SENT_AT)),
+            "missing timestamp"
+        );
+        assert_eq!(
+            rejection(handler.verify_at("t=soon,v0=00", BODY, SENT_AT)),
+            "malformed timestamp"
+        );
+        let header = format!("t={},v0=zz{}", SENT_AT, &SIGNATURE[2..]);
+        assert_eq!(
+            rejection(handler.verify_at(&header, BODY, SENT_AT)),
+            "signature mismatch"
+        );
+        let header = format!("t={},v0={}", SENT_AT, &SIGNATURE[1..]);
+        assert_eq!(
+            rejection(handler.verify_at(&header, BODY, SENT_AT)),
+            "signature mismatch"
+        );
+    }
+
+    #[test]
+    fn handle_runs_matching_callbacks() {
+        let matched = Rc::new(Cell::new(0));
+        let other = Rc::new(Cell::new(0));
+        let any = Rc::new(Cell::new(0));
+        let handler = WebhookHandler::new(SECRET)
+            .on("post_call_transcription", {
+                let matched = matched.clone();
+                move |_| matched.set(matched.get() + 1)
+            })
+            .on("voice_removal_notice", {
+                let other = other.clone();
+                move |_| other.set(other.get() + 1)
+            })
+            .on_any({
+                let any = any.clone();
+                move |_| any.set(any.get() + 1)
+            });
+
+        let now = now_secs();
+        let mut message = format!("{}.", now).into_bytes();
+        message.extend_from_slice(BODY);
+        let header = format!(
+            "t={},v0={}",
+            now,
+            to_hex(&hmac_sha256(SECRET.as_bytes(), &message))
+        );
+        let event = handler.handle(Some(&header), BODY).unwrap();
+        assert_eq!(event.event_type, "post_call_transcription");
+        assert_eq!((matched.get(), other.get(), any.get()), (1, 0, 1));
+
+        assert!(handler.handle(None, BODY).is_err());
+    }
+}